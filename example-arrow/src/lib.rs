@@ -333,8 +333,10 @@ macro_rules! value_fns {
             pub(crate) fn values_ba(array: &ArrayRef, default: $typ) -> Option<BigArray1<$typ>> {
                 let array = array.inner();
                 array.as_any().downcast_ref::<arrow::array::$array_typ>().map(|x| {
+                    // The arrow buffer is shared and immutable whereas OCaml can write to
+                    // the bigarray, so the values are copied.
                     let mut ba = BigArray1::new(x.values());
-                    if x.null_count() > 1 {
+                    if x.null_count() > 0 {
                         let data = ba.data_mut();
                         for (i, v) in data.iter_mut().enumerate() {
                             if x.is_null(i) {
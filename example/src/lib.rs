@@ -1,5 +1,5 @@
 #![allow(clippy::type_complexity)]
-use ocaml_rust::closure::{Fn0, Fn1};
+use ocaml_rust::closure::{Fn0, Fn1, Fn2, Fn4, RustFn1};
 
 fn option_result(v: Option<isize>, e: String) -> Result<isize, String> {
     match v {
@@ -18,6 +18,14 @@ fn add_i64(x: i64, y: i64) -> i64 {
     x + y
 }
 
+fn add_f64(x: f64, y: f64) -> f64 {
+    x + y
+}
+
+fn is_even(x: isize) -> bool {
+    x % 2 == 0
+}
+
 fn str_format(x: (isize, isize), y: String) -> String {
     format!("foo<{}|{}>: {}", x.0, x.1, y)
 }
@@ -30,16 +38,29 @@ fn vec_add(x: Vec<isize>, y: isize) -> Vec<isize> {
     x.iter().map(|x| x + y).collect()
 }
 
+fn rust_panic(msg: String) -> isize {
+    panic!("{}", msg)
+}
+
+fn format6(a: isize, b: i64, c: f64, d: String, e: Option<isize>, f: bool) -> String {
+    format!("{} {} {} {} {:?} {}", a, b, c, d, e, f)
+}
+
 #[ocaml_rust::bridge]
 mod ffi {
     extern "Rust" {
         #[namespace = "foo"]
         fn add_one(x: isize) -> isize;
         fn add_i64(x: i64, y: i64) -> i64;
+        #[noalloc]
+        fn add_f64(x: f64, y: f64) -> f64;
+        fn is_even(x: isize) -> bool;
         fn str_format(x: (isize, isize), y: String) -> String;
         fn pair(xy: (String, f64, (isize, isize))) -> String;
         fn option_result(v: Option<isize>, e: String) -> Result<isize, String>;
         fn vec_add(x: Vec<isize>, y: isize) -> Vec<isize>;
+        fn rust_panic(msg: String) -> isize;
+        fn format6(a: isize, b: i64, c: f64, d: String, e: Option<isize>, f: bool) -> String;
     }
 }
 
@@ -203,23 +224,29 @@ fn generate(i0: isize) -> ((((i64, i64), Compact), i64), i64) {
     ((((a, b), CompactToken()), c), d)
 }
 
+use ocaml_rust::FloatArray;
+
 #[ocaml_rust::bridge]
 mod ffi_double_array {
     ocaml_include!("open! Sexplib.Conv");
 
     #[ocaml_deriving(sexp)]
     #[derive(Debug, Clone)]
+    #[repr(C)]
     struct Quaternion {
         a: f64,
         b: f64,
-        c: f32,
+        c: f64,
         d: f64,
     }
 
     extern "Rust" {
         fn add_ones(v: Vec<f64>) -> Vec<f64>;
         fn add_quat(q1: &Quaternion, q2: Quaternion) -> Quaternion;
-        fn create_quat(a: f64, b: f64, c: f32, d: f64) -> Quaternion;
+        fn create_quat(a: f64, b: f64, c: f64, d: f64) -> Quaternion;
+        fn quat_norm(q: &Quaternion) -> f64;
+        fn float_array_sum(v: &FloatArray) -> f64;
+        fn normalize_quats(v: &mut FloatArray);
     }
 }
 
@@ -227,11 +254,871 @@ fn add_ones(v: Vec<f64>) -> Vec<f64> {
     v.into_iter().map(|x| x + 1.).collect()
 }
 
-fn create_quat(a: f64, b: f64, c: f32, d: f64) -> Quaternion {
+fn create_quat(a: f64, b: f64, c: f64, d: f64) -> Quaternion {
     Quaternion { a, b, c, d }
 }
 
+fn quat_norm(q: &Quaternion) -> f64 {
+    (q.a * q.a + q.b * q.b + q.c * q.c + q.d * q.d).sqrt()
+}
+
+fn float_array_sum(v: &FloatArray) -> f64 {
+    v.iter().sum()
+}
+
+fn normalize_quats(v: &mut FloatArray) {
+    for q in v.as_records_mut::<Quaternion>() {
+        let norm = quat_norm(q);
+        *q = Quaternion { a: q.a / norm, b: q.b / norm, c: q.c / norm, d: q.d / norm };
+    }
+}
+
 fn add_quat(q1: &Quaternion, q2: Quaternion) -> Quaternion {
     println!("{:?} {:?}", q1, q2);
     Quaternion { a: q1.a + q2.a, b: q1.b + q2.b, c: q1.c + q2.c, d: q1.d + q2.d }
 }
+
+#[ocaml_rust::bridge]
+mod ffi_ocaml {
+    extern "OCaml" {
+        fn ocaml_format(x: isize, y: &String) -> String;
+        /// Increments its argument, this can raise an exception.
+        fn ocaml_incr(x: isize) -> isize;
+    }
+
+    extern "Rust" {
+        fn call_ocaml_format(x: isize) -> String;
+        fn call_ocaml_incr(x: isize) -> Result<isize, String>;
+    }
+}
+
+fn call_ocaml_format(x: isize) -> String {
+    ocaml_format(x, &"from-rust".to_string()).unwrap()
+}
+
+fn call_ocaml_incr(x: isize) -> Result<isize, String> {
+    ocaml_incr(x).map_err(|err| format!("{:?}", err))
+}
+
+use ocaml_rust::RustResult;
+
+#[derive(Debug)]
+struct ConfigError(std::io::Error);
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "cannot read config")
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+#[ocaml_rust::bridge]
+mod ffi_errors {
+    extern "Rust" {
+        #[rust_error = "record"]
+        fn parse_int_record(s: String) -> RustResult<isize>;
+        #[rust_error = "exception"]
+        fn parse_int_exn(s: String) -> RustResult<isize>;
+        #[rust_error = "record"]
+        fn read_config(path: String) -> RustResult<String>;
+        /// Checks that the port is in 1..65535 (*inclusive*), the error "kind
+        /// depends on {|register_error_kinds|} having been called, '"' and "C:\
+        #[rust_error = "record"]
+        fn check_port(port: isize) -> RustResult<isize>;
+        fn register_error_kinds();
+    }
+}
+
+fn parse_int_record(s: String) -> RustResult<isize> {
+    Ok(s.parse()?)
+}
+
+fn parse_int_exn(s: String) -> RustResult<isize> {
+    Ok(s.parse()?)
+}
+
+fn read_config(path: String) -> RustResult<String> {
+    std::fs::read_to_string(path).map_err(|err| ConfigError(err).into())
+}
+
+#[derive(Debug)]
+struct PortError(isize);
+
+impl std::fmt::Display for PortError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid port {}", self.0)
+    }
+}
+
+impl std::error::Error for PortError {}
+
+fn check_port(port: isize) -> RustResult<isize> {
+    if (1..65536).contains(&port) {
+        Ok(port)
+    } else {
+        Err(PortError(port).into())
+    }
+}
+
+fn register_error_kinds() {
+    ocaml_rust::error::register_error_kind::<PortError>(|_| {
+        Some(ocaml_rust::error::ErrorKind::Parse)
+    })
+}
+
+use ocaml_rust::VecList;
+
+#[ocaml_rust::bridge]
+mod ffi_list {
+    ocaml_include!("open! Sexplib.Conv");
+
+    /// A shape given by the list of its vertices.
+    #[ocaml_deriving(sexp)]
+    #[derive(Debug, Clone)]
+    enum Shape {
+        Empty,
+        /// The vertices are given in clockwise order.
+        Polygon(VecList<(f64, f64)>),
+    }
+
+    /// A named series of values,
+    /// e.g. a column from a dataframe.
+    #[ocaml_deriving(sexp)]
+    #[derive(Debug, Clone)]
+    struct Series {
+        name: String,
+        /// Missing values are represented by nan.
+        values: VecList<f64>,
+    }
+
+    extern "Rust" {
+        /// Adds `y` to all the elements of the list.
+        fn list_add(vs: VecList<isize>, y: isize) -> VecList<isize>;
+        #[deprecated(note = "use list_add instead")]
+        fn list_incr(vs: VecList<isize>) -> VecList<isize>;
+        fn list_to_f32(vs: &VecList<f64>) -> VecList<f32>;
+        fn series_scale(s: &Series, factor: f64) -> Series;
+        fn shape_translate(s: &Shape, dx: f64, dy: f64) -> Shape;
+        fn list_callback(n: isize, f: &mut Fn1<VecList<isize>, isize>) -> isize;
+    }
+}
+
+fn list_add(vs: VecList<isize>, y: isize) -> VecList<isize> {
+    vs.into_iter().map(|x| x + y).collect()
+}
+
+fn list_incr(vs: VecList<isize>) -> VecList<isize> {
+    list_add(vs, 1)
+}
+
+fn list_to_f32(vs: &VecList<f64>) -> VecList<f32> {
+    vs.iter().map(|&x| x as f32).collect()
+}
+
+fn series_scale(s: &Series, factor: f64) -> Series {
+    let values = s.values.iter().map(|x| x * factor).collect();
+    Series { name: s.name.to_string(), values }
+}
+
+fn shape_translate(s: &Shape, dx: f64, dy: f64) -> Shape {
+    match s {
+        Shape::Empty => Shape::Empty,
+        Shape::Polygon(ps) => Shape::Polygon(ps.iter().map(|(x, y)| (x + dx, y + dy)).collect()),
+    }
+}
+
+fn list_callback(n: isize, f: &mut Fn1<VecList<isize>, isize>) -> isize {
+    f.call1((0..n).collect()).unwrap()
+}
+
+#[ocaml_rust::bridge]
+mod ffi_closures {
+    extern "Rust" {
+        fn fold_left(vs: &Vec<isize>, init: String, f: &mut Fn2<String, isize, String>) -> String;
+        fn call_with4(f: &mut Fn4<isize, f64, String, bool, String>) -> String;
+    }
+}
+
+fn fold_left(vs: &[isize], init: String, f: &mut Fn2<String, isize, String>) -> String {
+    vs.iter().fold(init, |acc, &v| f.call2(acc, v).unwrap())
+}
+
+fn call_with4(f: &mut Fn4<isize, f64, String, bool, String>) -> String {
+    f.call4(42, 2.5, "foo".to_string(), true).unwrap()
+}
+
+#[ocaml_rust::bridge]
+mod ffi_rust_fn {
+    extern "Rust" {
+        fn counter(start: isize) -> RustFn1<(), isize>;
+        fn splitter(sep: String) -> RustFn1<String, Vec<String>>;
+        fn comparator(descending: bool) -> RustFn1<(isize, isize), isize>;
+        fn checked_sqrt() -> RustFn1<f64, f64>;
+        fn incr_after(f: Fn1<isize, isize>) -> RustFn1<isize, isize>;
+    }
+}
+
+fn counter(start: isize) -> RustFn1<(), isize> {
+    let mut next = start;
+    RustFn1::new(move |()| {
+        next += 1;
+        next - 1
+    })
+}
+
+fn splitter(sep: String) -> RustFn1<String, Vec<String>> {
+    RustFn1::new(move |s: String| s.split(sep.as_str()).map(String::from).collect())
+}
+
+fn comparator(descending: bool) -> RustFn1<(isize, isize), isize> {
+    RustFn1::new(move |(x, y): (isize, isize)| {
+        let ord = if descending { y.cmp(&x) } else { x.cmp(&y) };
+        ord as isize
+    })
+}
+
+fn checked_sqrt() -> RustFn1<f64, f64> {
+    RustFn1::new(|x: f64| {
+        if x < 0. {
+            panic!("negative input")
+        }
+        x.sqrt()
+    })
+}
+
+fn incr_after(mut f: Fn1<isize, isize>) -> RustFn1<isize, isize> {
+    RustFn1::new(move |x| f.call1(x).unwrap() + 1)
+}
+
+use ocaml_rust::OCamlResult;
+
+#[ocaml_rust::bridge]
+mod ffi_exn {
+    extern "Rust" {
+        fn describe_exn(f: &mut Fn0<()>) -> String;
+        fn double_or_raise(f: &mut Fn1<isize, isize>, x: isize) -> OCamlResult<isize>;
+    }
+}
+
+fn describe_exn(f: &mut Fn0<()>) -> String {
+    match f.call0() {
+        Ok(()) => "no exception".to_string(),
+        Err(exn) => {
+            let name = exn.constructor_name();
+            let name = name.rsplit('.').next().unwrap_or_default().to_string();
+            let payload = if exn.is_registered("test.foo") {
+                format!(" payload={}", exn.payload::<isize>().unwrap())
+            } else {
+                String::new()
+            };
+            format!("{} args={}{}", name, exn.num_args(), payload)
+        }
+    }
+}
+
+fn double_or_raise(f: &mut Fn1<isize, isize>, x: isize) -> OCamlResult<isize> {
+    let v = f.call1(x)?;
+    Ok(2 * v)
+}
+
+mod geometry {
+    /// A point in the plane, all-float records use the flat float layout.
+    #[derive(ocaml_rust::ToValue, ocaml_rust::FromValue, Debug, Clone, Copy)]
+    pub struct Vec2 {
+        pub x: f64,
+        pub y: f64,
+    }
+
+    #[derive(ocaml_rust::ToValue, ocaml_rust::FromValue, Debug, Clone, Copy)]
+    pub enum Direction {
+        North,
+        East,
+        South,
+        West,
+        Angle(f64),
+    }
+
+    #[derive(ocaml_rust::ToValue, ocaml_rust::FromValue, Debug, Clone)]
+    pub struct Located<T> {
+        pub at: Vec2,
+        pub value: T,
+    }
+}
+
+use geometry::{Direction, Located, Vec2};
+
+#[ocaml_rust::bridge]
+mod ffi_derive {
+    extern "Rust" {
+        fn vec2_move(v: Vec2, d: Direction, len: f64) -> Vec2;
+        fn vec2_direction(v: Vec2) -> Direction;
+        fn located_move(l: Located<String>, d: Direction) -> Located<String>;
+    }
+}
+
+fn vec2_move(v: Vec2, d: Direction, len: f64) -> Vec2 {
+    let angle = match d {
+        Direction::North => std::f64::consts::FRAC_PI_2,
+        Direction::East => 0.,
+        Direction::South => -std::f64::consts::FRAC_PI_2,
+        Direction::West => std::f64::consts::PI,
+        Direction::Angle(angle) => angle,
+    };
+    Vec2 { x: v.x + len * angle.cos(), y: v.y + len * angle.sin() }
+}
+
+fn located_move(l: Located<String>, d: Direction) -> Located<String> {
+    Located { at: vec2_move(l.at, d, 1.), value: l.value }
+}
+
+fn vec2_direction(v: Vec2) -> Direction {
+    match (v.x == 0., v.y == 0.) {
+        (true, false) if v.y > 0. => Direction::North,
+        (true, false) => Direction::South,
+        (false, true) if v.x > 0. => Direction::East,
+        (false, true) => Direction::West,
+        _ => Direction::Angle(v.y.atan2(v.x)),
+    }
+}
+
+#[ocaml_rust::bridge]
+mod ffi_newtypes {
+    #[ocaml_unboxed]
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy)]
+    struct Meters(f64);
+
+    #[ocaml_unboxed]
+    #[derive(Debug, Clone, Copy)]
+    struct UserId(isize);
+
+    #[derive(Debug, Clone, Copy)]
+    struct Seconds(f64);
+
+    #[derive(Debug, Clone)]
+    struct Pair(isize, String);
+
+    #[derive(Debug, Clone, Copy)]
+    struct Marker;
+
+    extern "Rust" {
+        fn meters_add(x: Meters, y: Meters) -> Meters;
+        fn meters_double(x: &Meters) -> Meters;
+        fn speed(d: Meters, t: Seconds) -> f64;
+        fn user_ids(first: UserId, n: isize) -> Vec<UserId>;
+        fn pair_swap(p: &Pair) -> Pair;
+        fn marker_name(m: Marker) -> String;
+        fn marker_new() -> Marker;
+    }
+}
+
+fn meters_add(x: Meters, y: Meters) -> Meters {
+    Meters(x.0 + y.0)
+}
+
+fn meters_double(x: &Meters) -> Meters {
+    Meters(2. * x.0)
+}
+
+fn speed(d: Meters, t: Seconds) -> f64 {
+    d.0 / t.0
+}
+
+fn user_ids(first: UserId, n: isize) -> Vec<UserId> {
+    (0..n).map(|i| UserId(first.0 + i)).collect()
+}
+
+fn pair_swap(p: &Pair) -> Pair {
+    Pair(-p.0, p.1.chars().rev().collect())
+}
+
+fn marker_name(m: Marker) -> String {
+    format!("{:?}", m)
+}
+
+fn marker_new() -> Marker {
+    Marker
+}
+
+#[ocaml_rust::bridge]
+mod ffi_generics {
+    #[derive(Debug, Clone)]
+    struct Tagged<T> {
+        tag: String,
+        value: T,
+    }
+
+    #[derive(Debug, Clone)]
+    enum Tree<T> {
+        Leaf,
+        Node(Box<Tree<T>>, T, Box<Tree<T>>),
+    }
+
+    extern "Rust" {
+        fn tagged_int(tag: String, value: isize) -> Tagged<isize>;
+        fn tagged_describe(t: &Tagged<Vec<f64>>) -> String;
+        fn tree_of_list(values: Vec<isize>) -> Tree<isize>;
+        fn tree_sum(t: &Tree<isize>) -> isize;
+        fn tree_lengths(t: &Tree<String>) -> Tree<Tagged<usize>>;
+    }
+}
+
+fn tagged_int(tag: String, value: isize) -> Tagged<isize> {
+    Tagged { tag, value }
+}
+
+fn tagged_describe(t: &Tagged<Vec<f64>>) -> String {
+    format!("{}: {:?}", t.tag, t.value)
+}
+
+fn tree_insert(t: Tree<isize>, v: isize) -> Tree<isize> {
+    match t {
+        Tree::Leaf => Tree::Node(Box::new(Tree::Leaf), v, Box::new(Tree::Leaf)),
+        Tree::Node(l, x, r) if v < x => Tree::Node(Box::new(tree_insert(*l, v)), x, r),
+        Tree::Node(l, x, r) => Tree::Node(l, x, Box::new(tree_insert(*r, v))),
+    }
+}
+
+fn tree_of_list(values: Vec<isize>) -> Tree<isize> {
+    values.into_iter().fold(Tree::Leaf, tree_insert)
+}
+
+fn tree_sum(t: &Tree<isize>) -> isize {
+    match t {
+        Tree::Leaf => 0,
+        Tree::Node(l, x, r) => tree_sum(l) + x + tree_sum(r),
+    }
+}
+
+fn tree_lengths(t: &Tree<String>) -> Tree<Tagged<usize>> {
+    match t {
+        Tree::Leaf => Tree::Leaf,
+        Tree::Node(l, s, r) => Tree::Node(
+            Box::new(tree_lengths(l)),
+            Tagged { tag: s.clone(), value: s.len() },
+            Box::new(tree_lengths(r)),
+        ),
+    }
+}
+
+#[ocaml_rust::bridge]
+mod ffi_polyvar {
+    ocaml_include!("open! Sexplib.Conv");
+
+    #[ocaml(polymorphic_variant)]
+    #[ocaml_deriving(sexp)]
+    #[derive(Debug, Clone)]
+    enum OpenFlag {
+        Read,
+        Write,
+        Append,
+        Mode(isize),
+        Rename(String, String),
+    }
+
+    extern "Rust" {
+        fn open_flags_default() -> Vec<OpenFlag>;
+        fn open_flags_describe(flags: Vec<OpenFlag>) -> String;
+        fn open_flag_bump(flag: OpenFlag) -> OpenFlag;
+    }
+}
+
+fn open_flags_default() -> Vec<OpenFlag> {
+    vec![OpenFlag::Read, OpenFlag::Mode(0o644)]
+}
+
+fn open_flags_describe(flags: Vec<OpenFlag>) -> String {
+    let flags: Vec<String> = flags.iter().map(|flag| format!("{:?}", flag)).collect();
+    flags.join(",")
+}
+
+fn open_flag_bump(flag: OpenFlag) -> OpenFlag {
+    match flag {
+        OpenFlag::Read => OpenFlag::Write,
+        OpenFlag::Write => OpenFlag::Append,
+        OpenFlag::Append => OpenFlag::Read,
+        OpenFlag::Mode(mode) => OpenFlag::Mode(mode + 1),
+        OpenFlag::Rename(src, dst) => OpenFlag::Rename(dst, src),
+    }
+}
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+#[ocaml_rust::bridge]
+mod ffi_maps {
+    ocaml_include!("open! Sexplib.Conv");
+
+    #[ocaml_deriving(sexp)]
+    #[derive(Debug, Clone)]
+    struct Metadata {
+        name: String,
+        entries: BTreeMap<String, String>,
+    }
+
+    extern "Rust" {
+        fn word_counts(text: String) -> BTreeMap<String, isize>;
+        fn merge_counts(
+            c1: HashMap<String, isize>,
+            c2: HashMap<String, isize>,
+        ) -> BTreeMap<String, isize>;
+        fn unique_sorted(xs: Vec<isize>) -> BTreeSet<isize>;
+        fn set_contains(set: HashSet<String>, x: String) -> bool;
+        fn metadata_set(m: Metadata, key: String, value: String) -> Metadata;
+    }
+}
+
+fn word_counts(text: String) -> BTreeMap<String, isize> {
+    let mut counts = BTreeMap::new();
+    for word in text.split_whitespace() {
+        *counts.entry(word.to_string()).or_insert(0) += 1;
+    }
+    counts
+}
+
+fn merge_counts(c1: HashMap<String, isize>, c2: HashMap<String, isize>) -> BTreeMap<String, isize> {
+    let mut counts: BTreeMap<String, isize> = c1.into_iter().collect();
+    for (key, value) in c2.into_iter() {
+        *counts.entry(key).or_insert(0) += value;
+    }
+    counts
+}
+
+fn unique_sorted(xs: Vec<isize>) -> BTreeSet<isize> {
+    xs.into_iter().collect()
+}
+
+fn set_contains(set: HashSet<String>, x: String) -> bool {
+    set.contains(&x)
+}
+
+fn metadata_set(mut m: Metadata, key: String, value: String) -> Metadata {
+    m.entries.insert(key, value);
+    m
+}
+
+use ocaml_rust::Bytes;
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+#[ocaml_rust::bridge]
+mod ffi_bytes {
+    extern "Rust" {
+        fn bytes_xor(data: &mut [u8], key: isize);
+        fn bytes_checksum(data: &[u8]) -> isize;
+        fn bytes_copy(src: &[u8], dst: &mut [u8]) -> isize;
+        fn bytes_reverse(b: Bytes) -> Bytes;
+        fn lossy_length(s: String) -> isize;
+        #[strict_utf8]
+        fn utf8_length(s: String) -> isize;
+        fn os_string_len(s: OsString) -> isize;
+        fn path_join(dir: PathBuf, file: OsString) -> PathBuf;
+    }
+}
+
+fn bytes_xor(data: &mut [u8], key: isize) {
+    for b in data.iter_mut() {
+        *b ^= key as u8
+    }
+}
+
+fn bytes_checksum(data: &[u8]) -> isize {
+    data.iter().fold(0, |acc, b| (acc * 31 + *b as isize) % 65521)
+}
+
+fn bytes_copy(src: &[u8], dst: &mut [u8]) -> isize {
+    let len = std::cmp::min(src.len(), dst.len());
+    dst[..len].copy_from_slice(&src[..len]);
+    len as isize
+}
+
+fn bytes_reverse(mut b: Bytes) -> Bytes {
+    b.reverse();
+    b
+}
+
+fn lossy_length(s: String) -> isize {
+    s.chars().count() as isize
+}
+
+fn utf8_length(s: String) -> isize {
+    s.chars().count() as isize
+}
+
+fn os_string_len(s: OsString) -> isize {
+    s.len() as isize
+}
+
+fn path_join(dir: PathBuf, file: OsString) -> PathBuf {
+    dir.join(file)
+}
+
+use ocaml_rust::OCamlInt;
+
+#[ocaml_rust::bridge]
+mod ffi_borrowed {
+    extern "Rust" {
+        fn str_word_count(s: &str) -> isize;
+        fn str_contains(haystack: &str, needle: &str) -> bool;
+        fn floats_sum(xs: &[f64]) -> f64;
+        fn floats_scale(xs: &mut [f64], factor: f64);
+        fn ints_sum(xs: &[OCamlInt]) -> isize;
+        fn ints_incr(xs: &mut [OCamlInt]);
+        fn str_len_with(s: &str, f: &mut Fn1<isize, isize>) -> isize;
+        fn str_to_rooted(s: &str) -> isize;
+    }
+}
+
+fn str_word_count(s: &str) -> isize {
+    s.split_whitespace().count() as isize
+}
+
+fn str_contains(haystack: &str, needle: &str) -> bool {
+    haystack.contains(needle)
+}
+
+fn floats_sum(xs: &[f64]) -> f64 {
+    xs.iter().sum()
+}
+
+fn floats_scale(xs: &mut [f64], factor: f64) {
+    for x in xs.iter_mut() {
+        *x *= factor
+    }
+}
+
+fn ints_sum(xs: &[OCamlInt]) -> isize {
+    xs.iter().map(|x| x.get()).sum()
+}
+
+fn ints_incr(xs: &mut [OCamlInt]) {
+    for x in xs.iter_mut() {
+        x.set(x.get() + 1)
+    }
+}
+
+// Calling the closure could move the string so this raises.
+fn str_len_with(s: &str, f: &mut Fn1<isize, isize>) -> isize {
+    f.call1(s.len() as isize).unwrap()
+}
+
+fn str_to_rooted(s: &str) -> isize {
+    let v = ocaml_rust::to_value::to_rooted_value(&s.to_string());
+    v.value().value as isize
+}
+
+use ocaml_rust::{BigArray2, BigArray3, FortranLayout, Genarray};
+
+#[ocaml_rust::bridge]
+mod ffi_bigarray {
+    extern "Rust" {
+        fn matrix_transpose(m: BigArray2<f64>) -> BigArray2<f64>;
+        fn matrix_trace(m: BigArray2<f64, FortranLayout>) -> f64;
+        fn matrix_strides(m: BigArray2<f64, FortranLayout>) -> Vec<isize>;
+        fn cube_new(n: isize) -> BigArray3<f32, FortranLayout>;
+        fn genarray_shape(g: Genarray<f64>) -> Vec<isize>;
+        fn genarray_sum(g: Genarray<f64>) -> f64;
+        fn matrix_row_sums(m: BigArray2<f64, FortranLayout>) -> Vec<f64>;
+        fn genarray_scale(g: Genarray<f64>, factor: f64);
+        fn bigarray_copy(dst: BigArray1<f64>, src: BigArray1<f64>);
+    }
+}
+
+fn matrix_transpose(m: BigArray2<f64>) -> BigArray2<f64> {
+    let shape = m.shape();
+    let (rows, cols) = (shape[0], shape[1]);
+    let mut vs = Vec::with_capacity(rows * cols);
+    for j in 0..cols {
+        for i in 0..rows {
+            vs.push(*m.get([i, j]).unwrap())
+        }
+    }
+    BigArray2::new(&vs, [cols, rows])
+}
+
+fn matrix_trace(m: BigArray2<f64, FortranLayout>) -> f64 {
+    let shape = m.shape();
+    (0..std::cmp::min(shape[0], shape[1])).map(|i| m.get([i, i]).unwrap()).sum()
+}
+
+fn matrix_strides(m: BigArray2<f64, FortranLayout>) -> Vec<isize> {
+    m.strides().into_iter().map(|s| s as isize).collect()
+}
+
+fn cube_new(n: isize) -> BigArray3<f32, FortranLayout> {
+    let n = n as usize;
+    let mut vs = Vec::with_capacity(n * n * n);
+    for k in 0..n {
+        for j in 0..n {
+            for i in 0..n {
+                vs.push((i + 10 * j + 100 * k) as f32)
+            }
+        }
+    }
+    BigArray3::new(&vs, [n, n, n])
+}
+
+fn genarray_shape(g: Genarray<f64>) -> Vec<isize> {
+    g.shape().into_iter().map(|d| d as isize).collect()
+}
+
+fn genarray_sum(g: Genarray<f64>) -> f64 {
+    g.data().iter().sum()
+}
+
+fn matrix_row_sums(m: BigArray2<f64, FortranLayout>) -> Vec<f64> {
+    m.view().sum_axis(ndarray::Axis(1)).to_vec()
+}
+
+fn genarray_scale(mut g: Genarray<f64>, factor: f64) {
+    g.view_mut().mapv_inplace(|x| x * factor)
+}
+
+fn bigarray_copy(mut dst: BigArray1<f64>, src: BigArray1<f64>) {
+    dst.data_mut().copy_from_slice(src.data())
+}
+
+use ocaml_rust::bigarray::{Complex32, Complex64, NativeInt, OCamlChar};
+use ocaml_rust::BigArray1;
+
+#[ocaml_rust::bridge]
+mod ffi_bigarray_kinds {
+    extern "Rust" {
+        fn int16_mean(xs: BigArray1<i16>) -> f64;
+        fn uint16_max(xs: BigArray1<u16>) -> isize;
+        fn int8_abs(xs: BigArray1<i8>) -> BigArray1<i8>;
+        fn int64_sum(xs: BigArray1<i64>) -> i64;
+        fn iq_power(xs: BigArray1<Complex32>) -> BigArray1<f32>;
+        fn complex64_conj(xs: BigArray1<Complex64>) -> BigArray1<Complex64>;
+        fn chars_uppercase(xs: BigArray1<OCamlChar>) -> BigArray1<OCamlChar>;
+        fn ints_double(xs: BigArray1<isize>) -> BigArray1<NativeInt>;
+    }
+}
+
+fn int16_mean(xs: BigArray1<i16>) -> f64 {
+    let xs = xs.data();
+    xs.iter().map(|&x| x as f64).sum::<f64>() / xs.len() as f64
+}
+
+fn uint16_max(xs: BigArray1<u16>) -> isize {
+    xs.data().iter().max().map_or(0, |&x| x as isize)
+}
+
+fn int8_abs(xs: BigArray1<i8>) -> BigArray1<i8> {
+    let vs: Vec<i8> = xs.data().iter().map(|x| x.wrapping_abs()).collect();
+    BigArray1::new(&vs)
+}
+
+fn int64_sum(xs: BigArray1<i64>) -> i64 {
+    xs.data().iter().sum()
+}
+
+fn iq_power(xs: BigArray1<Complex32>) -> BigArray1<f32> {
+    let vs: Vec<f32> = xs.data().iter().map(|x| x.norm_sqr()).collect();
+    BigArray1::new(&vs)
+}
+
+fn complex64_conj(xs: BigArray1<Complex64>) -> BigArray1<Complex64> {
+    let vs: Vec<Complex64> = xs.data().iter().map(|x| x.conj()).collect();
+    BigArray1::new(&vs)
+}
+
+fn chars_uppercase(xs: BigArray1<OCamlChar>) -> BigArray1<OCamlChar> {
+    let vs: Vec<OCamlChar> =
+        xs.data().iter().map(|c| OCamlChar(c.0.to_ascii_uppercase())).collect();
+    BigArray1::new(&vs)
+}
+
+fn ints_double(xs: BigArray1<isize>) -> BigArray1<NativeInt> {
+    let vs: Vec<NativeInt> = xs.data().iter().map(|x| NativeInt(2 * x)).collect();
+    BigArray1::new(&vs)
+}
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[ocaml_rust::bridge]
+mod ffi_bigarray_owned {
+    extern "Rust" {
+        fn range_vec(n: isize) -> BigArray1<f64>;
+        fn squares_boxed(n: isize) -> BigArray1<i64>;
+        fn arc_matrix(rows: isize, cols: isize) -> BigArray2<f32>;
+        fn tracked_buffer(n: isize) -> BigArray1<f64>;
+        fn tracked_drops() -> isize;
+    }
+}
+
+fn range_vec(n: isize) -> BigArray1<f64> {
+    let vs: Vec<f64> = (0..n).map(|i| i as f64).collect();
+    let len = vs.len();
+    BigArray1::from_owner(vs, [len])
+}
+
+fn squares_boxed(n: isize) -> BigArray1<i64> {
+    let vs: Box<[i64]> = (0..n as i64).map(|i| i * i).collect();
+    let len = vs.len();
+    BigArray1::from_owner(vs, [len])
+}
+
+// The data is shared with the Arc so the bigarray is only read on the OCaml side.
+fn arc_matrix(rows: isize, cols: isize) -> BigArray2<f32> {
+    let (rows, cols) = (rows as usize, cols as usize);
+    let vs: std::sync::Arc<[f32]> = (0..rows * cols).map(|i| i as f32).collect();
+    let data = vs.as_ptr() as *mut f32;
+    unsafe { BigArray2::from_raw_parts(data, [rows, cols], vs) }
+}
+
+static TRACKED_DROPS: AtomicUsize = AtomicUsize::new(0);
+
+struct Tracked(Vec<f64>);
+
+impl AsMut<[f64]> for Tracked {
+    fn as_mut(&mut self) -> &mut [f64] {
+        &mut self.0
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        TRACKED_DROPS.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn tracked_buffer(n: isize) -> BigArray1<f64> {
+    BigArray1::from_owner(Tracked(vec![1.5; n as usize]), [n as usize])
+}
+
+fn tracked_drops() -> isize {
+    TRACKED_DROPS.load(Ordering::SeqCst) as isize
+}
+
+#[ocaml_rust::bridge]
+mod ffi_bigarray_parallel {
+    extern "Rust" {
+        #[release_runtime_lock]
+        fn bigarray_scale(xs: &mut BigArray1<f64>, factor: f64);
+        #[release_runtime_lock]
+        fn bigarray_add(dst: &mut BigArray1<f64>, src: &BigArray1<f64>);
+    }
+}
+
+// The slices do not depend on the OCaml runtime so they can be processed by
+// other threads while the runtime lock is released.
+fn bigarray_scale(xs: &mut BigArray1<f64>, factor: f64) {
+    let data = xs.data_mut();
+    let chunk_size = std::cmp::max(1, data.len() / 4);
+    std::thread::scope(|s| {
+        for chunk in data.chunks_mut(chunk_size) {
+            s.spawn(move || chunk.iter_mut().for_each(|x| *x *= factor));
+        }
+    })
+}
+
+fn bigarray_add(dst: &mut BigArray1<f64>, src: &BigArray1<f64>) {
+    for (d, s) in dst.data_mut().iter_mut().zip(src.data().iter()) {
+        *d += s
+    }
+}
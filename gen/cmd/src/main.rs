@@ -35,6 +35,19 @@ struct Args {
     /// OCaml file to generate
     #[clap(short, long)]
     ocaml_file: String,
+
+    /// OCaml interface file to generate
+    #[clap(long)]
+    mli_file: Option<String>,
+
+    /// Generate a Rust_base module converting maps and sets to Base containers
+    #[clap(long)]
+    base_helpers: bool,
+
+    /// Name of the crate containing the Rust file, by default this is read from
+    /// the closest Cargo.toml
+    #[clap(long)]
+    crate_name: Option<String>,
 }
 
 fn capitalize(s: &str) -> String {
@@ -73,19 +86,90 @@ fn ocaml_deriving(attrs: &[Attribute]) -> String {
     }
 }
 
-struct InScope {
-    items: Vec<ModItem>,
-    inner: BTreeMap<String, InScope>,
+// OCaml comments can be nested and the string literals they contain are lexed,
+// so comment delimiters and quoted string openings are split with a space and
+// a string literal that is still open at the end of a line gets closed there.
+fn escape_doc_line(line: &str) -> String {
+    let line = line.replace("(*", "( *").replace("*)", "* )");
+    let mut escaped = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        escaped.push(c);
+        if c == '{' {
+            let mut id = String::new();
+            while let Some(c) = chars.next_if(|c| c.is_ascii_lowercase() || *c == '_') {
+                id.push(c)
+            }
+            if chars.peek() == Some(&'|') {
+                escaped.push(' ')
+            }
+            escaped.push_str(&id)
+        }
+    }
+    let mut in_string = false;
+    let mut dangling_backslash = false;
+    let mut chars = escaped.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if in_string => dangling_backslash = chars.next().is_none(),
+            '"' => in_string = !in_string,
+            // Character literals do not start a string.
+            '\'' if !in_string => {
+                let mut lookahead = chars.clone();
+                if lookahead.next() == Some('"') && lookahead.next() == Some('\'') {
+                    chars = lookahead
+                }
+            }
+            _ => {}
+        }
+    }
+    // A backslash at the end of the string would escape the closing quote.
+    if dangling_backslash {
+        escaped.push('\\')
+    }
+    if in_string {
+        escaped.push('"')
+    }
+    escaped
+}
+
+// Doc comments for items are preceded by an empty line as otherwise they could
+// also be attached to the previous item, see warning 50.
+fn doc_comment(lines: &[String], indent: &str) -> Option<String> {
+    if lines.is_empty() {
+        return None;
+    }
+    let lines: Vec<String> = lines.iter().map(|line| escape_doc_line(line)).collect();
+    let sep = format!("\n{}    ", indent);
+    Some(format!("{}(** {} *)", indent, lines.join(&sep)))
+}
+
+// Maps #[deprecated] to the OCaml attribute, at is "@" for fields and
+// constructors and "@@" for items.
+fn deprecated_attr(note: Option<&str>, at: &str) -> String {
+    match note {
+        None => "".to_string(),
+        Some("") => format!("[{}ocaml.deprecated]", at),
+        Some(note) => {
+            let note = note.replace('\\', "\\\\").replace('"', "\\\"");
+            format!("[{}ocaml.deprecated \"{}\"]", at, note)
+        }
+    }
 }
 
-impl InScope {
+struct InScope<'a> {
+    items: Vec<&'a ModItem>,
+    inner: BTreeMap<String, InScope<'a>>,
+}
+
+impl<'a> InScope<'a> {
     fn new() -> Self {
         Self { items: vec![], inner: BTreeMap::new() }
     }
 
-    fn insert(&mut self, item: ModItem) {
-        fn insert_loop(s: &mut InScope, item: ModItem, index: usize) {
-            let namespace = match &item {
+    fn insert(&mut self, item: &'a ModItem) {
+        fn insert_loop<'a>(s: &mut InScope<'a>, item: &'a ModItem, index: usize) {
+            let namespace = match item {
                 ModItem::Fn { attrs, .. } => attrs.namespace.as_ref().unwrap(),
             };
             if index >= namespace.len() {
@@ -96,7 +180,7 @@ impl InScope {
                 insert_loop(s, item, index + 1)
             }
         }
-        let attrs_is_none = match &item {
+        let attrs_is_none = match item {
             ModItem::Fn { attrs, .. } => attrs.namespace.is_none(),
         };
         if attrs_is_none {
@@ -106,149 +190,739 @@ impl InScope {
         }
     }
 
+    // The same external declarations are used in the interface file, this way the
+    // unboxed and noalloc attributes are visible to other modules.
     fn write<W: Write>(
         &self,
         w: &mut W,
         api_ident: &proc_macro2::Ident,
+        sig: bool,
+        base_helpers: bool,
     ) -> Result<(), syntax::Error> {
         for item in self.items.iter() {
             match item {
                 ModItem::Fn { ident, args, output, attrs } => {
-                    let args = if !args.is_empty() {
-                        let args: Result<Vec<std::string::String>, syn::parse::Error> = args
-                            .iter()
-                            .map(|(_ident, _ty, typ)| Ok(typ.to_ocaml_string()))
-                            .collect();
-                        args?.join(" -> ")
-                    } else {
-                        "unit".to_string()
+                    let nargs = args.len();
+                    let unboxed_signature = item.unboxed_signature();
+                    let (args, output) = match &unboxed_signature {
+                        None => {
+                            let args: Vec<_> = args
+                                .iter()
+                                .map(|(_ident, _ty, typ)| typ.to_ocaml_string())
+                                .collect();
+                            (args, output.1.to_ocaml_output_string(attrs.rust_error))
+                        }
+                        Some((args, output)) => (
+                            args.iter().map(|x| x.to_ocaml_string()).collect(),
+                            output.to_ocaml_string(),
+                        ),
                     };
-                    let output = output.1.to_ocaml_string();
+                    let args =
+                        if !args.is_empty() { args.join(" -> ") } else { "unit".to_string() };
+                    if let Some(doc) = doc_comment(&attrs.docs, "  ") {
+                        // Items in the interface are always followed by an empty line.
+                        let sep = if sig { "" } else { "\n" };
+                        writeln!(w, "{}{}", sep, doc)?;
+                    }
                     writeln!(w, "  external {}", ident)?;
                     writeln!(w, "    : {} -> {}", args, output)?;
-                    writeln!(
-                        w,
-                        "    = \"{}\"\n  ;;\n",
-                        crate::syntax::expand::c_fn_name(
-                            api_ident,
-                            ident,
-                            attrs.namespace.as_ref()
-                        )
-                    )?;
+                    let namespace = attrs.namespace.as_ref();
+                    let c_fn_name = crate::syntax::expand::c_fn_name(api_ident, ident, namespace);
+                    let bytecode_fn_name = if crate::syntax::expand::needs_bytecode_stub(nargs) {
+                        Some(crate::syntax::expand::bytecode_fn_name(api_ident, ident, namespace))
+                    } else {
+                        None
+                    };
+                    match (bytecode_fn_name, unboxed_signature) {
+                        (None, None) => writeln!(w, "    = \"{}\"", c_fn_name)?,
+                        (Some(bytecode_fn_name), None) => {
+                            writeln!(w, "    = \"{}\" \"{}\"", bytecode_fn_name, c_fn_name)?
+                        }
+                        (bytecode_fn_name, Some(_)) => {
+                            let unboxed_fn_name =
+                                crate::syntax::expand::unboxed_fn_name(api_ident, ident, namespace);
+                            let bytecode_fn_name = bytecode_fn_name.unwrap_or(c_fn_name);
+                            writeln!(w, "    = \"{}\" \"{}\"", bytecode_fn_name, unboxed_fn_name)?
+                        }
+                    }
+                    if attrs.noalloc {
+                        writeln!(w, "  [@@noalloc]")?;
+                    }
+                    if attrs.deprecated.is_some() {
+                        writeln!(w, "  {}", deprecated_attr(attrs.deprecated.as_deref(), "@@"))?;
+                    }
+                    if sig {
+                        writeln!(w)?;
+                    } else {
+                        writeln!(w, "  ;;\n")?;
+                    }
                 }
             }
         }
+        if base_helpers {
+            write_base_wrappers(w, &self.items, sig)?;
+        }
         for (k, v) in self.inner.iter() {
-            writeln!(w, "module {} = struct", capitalize(k))?;
-            v.write(w, api_ident)?;
+            if sig {
+                writeln!(w, "module {} : sig", capitalize(k))?;
+            } else {
+                writeln!(w, "module {} = struct", capitalize(k))?;
+            }
+            v.write(w, api_ident, sig, base_helpers)?;
             writeln!(w, "end")?;
         }
         Ok(())
     }
 }
 
-fn try_main(args: Args) -> Result<(), syntax::Error> {
-    let rust_source = read_to_string(&args.rust_file)?;
-    proc_macro2::fallback::force();
-    let file: File = syn::parse_str(&rust_source)?;
-    let mut w = std::fs::File::create(args.ocaml_file)?;
-    for api in file.apis.into_iter() {
-        writeln!(w, "module {} = struct", capitalize(&api.ident.to_string()))?;
-        for api_item in api.api_items.iter() {
-            match api_item {
-                ApiItem::ForeignMod { .. } => {}
-                ApiItem::Enum(e) => {
-                    writeln!(w, "  type {} =", syntax::api::ocamlize(&e.ident.to_string()))?;
-                    for variant in e.variants.iter() {
-                        let variant_ident = capitalize(&variant.ident.to_string());
-                        let args = match &variant.fields {
-                            syn::Fields::Unit => "".to_string(),
-                            syn::Fields::Unnamed(u) => {
-                                let args: Result<Vec<String>, syntax::Error> = u
-                                    .unnamed
-                                    .iter()
-                                    .map(|x| {
-                                        Ok(syntax::api::Type::parse_type(&x.ty)?.to_ocaml_string())
-                                    })
-                                    .collect();
-                                let args = args?.join(" * ");
-                                format!(" of {}", args)
-                            }
-                            syn::Fields::Named(n) => {
-                                let args: Result<Vec<String>, syntax::Error> = n
-                                    .named
-                                    .iter()
-                                    .map(|x| {
-                                        let field_ident = match &x.ident {
-                                            None => {
-                                                let msg = format!(
-                                                    "struct with unnamed field {} in enum",
-                                                    variant_ident
-                                                );
-                                                return Err(syn::Error::new_spanned(&x, msg).into());
-                                            }
-                                            Some(ident) => ident.to_string(),
-                                        };
-                                        let ty =
-                                            syntax::api::Type::parse_type(&x.ty)?.to_ocaml_string();
-                                        Ok(format!("{}: {}", field_ident, ty))
-                                    })
-                                    .collect();
-                                let args = args?.join("; ");
-                                format!(" of {{ {} }}", args)
-                            }
-                        };
-                        writeln!(w, "  | {}{}", variant_ident, args)?;
-                    }
-                    let deriving = ocaml_deriving(&e.attrs);
-                    writeln!(w, "  [@@boxed]{};;", deriving)?;
+// The functions from extern "OCaml" blocks are called from Rust by looking
+// up their registered name, so generate a typed registration function for each.
+fn write_registers<W: Write>(
+    w: &mut W,
+    items: &[&ModItem],
+    api_ident: &proc_macro2::Ident,
+    sig: bool,
+) -> Result<(), syntax::Error> {
+    for item in items.iter() {
+        match item {
+            ModItem::Fn { ident, args, output, attrs } => {
+                let args = if !args.is_empty() {
+                    let args: Vec<_> =
+                        args.iter().map(|(_ident, _ty, typ)| typ.to_ocaml_string()).collect();
+                    args.join(" -> ")
+                } else {
+                    "unit".to_string()
+                };
+                let output = output.1.to_ocaml_string();
+                if let Some(doc) = doc_comment(&attrs.docs, "  ") {
+                    let sep = if sig { "" } else { "\n" };
+                    writeln!(w, "{}{}", sep, doc)?;
+                }
+                if sig {
+                    writeln!(w, "  val register_{} : ({} -> {}) -> unit\n", ident, args, output)?;
+                    continue;
                 }
-                ApiItem::Struct(s) => {
-                    writeln!(w, "  type {} = {{", syntax::api::ocamlize(&s.ident.to_string()))?;
-                    for field in s.fields.iter() {
-                        let ident = match &field.ident {
-                            None => {
-                                return Err(syn::Error::new_spanned(
-                                    &field,
-                                    format!("struct with unnamed field {}", s.ident),
-                                )
-                                .into())
-                            }
-                            Some(ident) => ident,
-                        };
-                        let ty = syntax::api::Type::parse_type(&field.ty)?.to_ocaml_string();
-                        writeln!(w, "    {}: {};", ident, ty)?;
+                writeln!(w, "  let register_{} (f : {} -> {}) =", ident, args, output)?;
+                writeln!(
+                    w,
+                    "    Callback.register \"{}\" f\n  ;;\n",
+                    crate::syntax::expand::c_fn_name(api_ident, ident, attrs.namespace.as_ref())
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+const RUST_ERROR_TYPES: &str = "  type kind =
+    | Io
+    | Parse
+    | Other
+
+  type t =
+    { kind : kind
+    ; message : string
+    ; sources : string array
+    }
+";
+
+// Maps and sets are converted to association lists and lists, these helpers
+// convert them to the Base containers. This is optional as it requires Base.
+const BASE_HELPERS: &str = "module Rust_base = struct
+  let map_of_alist m l = Base.Map.of_alist_exn m l
+  let map_to_alist m = Base.Map.to_alist m
+  let hashtbl_of_alist m l = Base.Hashtbl.of_alist_exn m l
+  let hashtbl_to_alist t = Base.Hashtbl.to_alist t
+  let set_of_list m l = Base.Set.of_list m l
+  let set_to_list s = Base.Set.to_list s
+end
+";
+
+const BASE_HELPERS_SIG: &str = "module Rust_base : sig
+  val map_of_alist
+    : ('k, 'cmp) Base.Comparator.Module.t -> ('k * 'v) list -> ('k, 'v, 'cmp) Base.Map.t
+
+  val map_to_alist : ('k, 'v, _) Base.Map.t -> ('k * 'v) list
+  val hashtbl_of_alist : 'k Base.Hashtbl.Key.t -> ('k * 'v) list -> ('k, 'v) Base.Hashtbl.t
+  val hashtbl_to_alist : ('k, 'v) Base.Hashtbl.t -> ('k * 'v) list
+  val set_of_list : ('a, 'cmp) Base.Comparator.Module.t -> 'a list -> ('a, 'cmp) Base.Set.t
+  val set_to_list : ('a, _) Base.Set.t -> 'a list
+end
+";
+
+// The Base module for keys of maps and sets returned by Rust, other key types
+// would require passing the comparator explicitly.
+fn base_key_module(ty: &syntax::api::Type) -> Option<&'static str> {
+    let module = match ty.to_ocaml_string().as_str() {
+        "int" => "Base.Int",
+        "Int32.t" => "Base.Int32",
+        "Int64.t" => "Base.Int64",
+        "float" => "Base.Float",
+        "string" => "Base.String",
+        "char" => "Base.Char",
+        "bool" => "Base.Bool",
+        _ => return None,
+    };
+    Some(module)
+}
+
+// The Base container type for a map or set, together with the function
+// converting it to a list and, when the key type is supported, the function
+// converting a list to it.
+fn base_container(ty: &syntax::api::Type) -> Option<(String, String, Option<String>)> {
+    use syntax::api::Type;
+    let (ty, to_list, of_list) = match ty {
+        Type::Map(key, value, hashed) => {
+            let module = base_key_module(key);
+            let (key, value) = (key.to_ocaml_string(), value.to_ocaml_string());
+            if *hashed {
+                let ty = format!("({}, {}) Base.Hashtbl.t", key, value);
+                let of_list = module.map(|m| format!("Base.Hashtbl.of_alist_exn (module {})", m));
+                (ty, "Base.Hashtbl.to_alist", of_list)
+            } else {
+                let cmp = module.map_or("_".to_string(), |m| format!("{}.comparator_witness", m));
+                let ty = format!("({}, {}, {}) Base.Map.t", key, value, cmp);
+                let of_list = module.map(|m| format!("Base.Map.of_alist_exn (module {})", m));
+                (ty, "Base.Map.to_alist", of_list)
+            }
+        }
+        Type::Set(elt, hashed) => {
+            let module = base_key_module(elt);
+            let elt = elt.to_ocaml_string();
+            if *hashed {
+                let ty = format!("{} Base.Hash_set.t", elt);
+                let of_list = module.map(|m| format!("Base.Hash_set.of_list (module {})", m));
+                (ty, "Base.Hash_set.to_list", of_list)
+            } else {
+                let cmp = module.map_or("_".to_string(), |m| format!("{}.comparator_witness", m));
+                let ty = format!("({}, {}) Base.Set.t", elt, cmp);
+                let of_list = module.map(|m| format!("Base.Set.of_list (module {})", m));
+                (ty, "Base.Set.to_list", of_list)
+            }
+        }
+        _ => return None,
+    };
+    Some((ty, to_list.to_string(), of_list))
+}
+
+// With --base-helpers, the functions taking or returning maps and sets get a
+// wrapper in a With_base submodule that uses the Base containers rather than
+// lists. Functions returning a container with an unsupported key type have no
+// wrapper.
+fn write_base_wrappers<W: Write>(
+    w: &mut W,
+    items: &[&ModItem],
+    sig: bool,
+) -> Result<(), syntax::Error> {
+    let mut wrappers = vec![];
+    for item in items.iter() {
+        let ModItem::Fn { ident, args, output, attrs } = item;
+        let containers: Vec<_> = args.iter().map(|(_, _, ty)| base_container(ty)).collect();
+        let output_container = base_container(&output.1);
+        if containers.iter().all(Option::is_none) && output_container.is_none() {
+            continue;
+        }
+        let (output_ty, output_of_list) = match output_container {
+            None => (output.1.to_ocaml_output_string(attrs.rust_error), None),
+            Some((_, _, None)) => continue,
+            Some((ty, _, Some(of_list))) => (ty, Some(of_list)),
+        };
+        let args_ty: Vec<String> = args
+            .iter()
+            .zip(containers.iter())
+            .map(|((_, _, ty), container)| match container {
+                None => ty.to_ocaml_string(),
+                Some((ty, _, _)) => ty.clone(),
+            })
+            .collect();
+        let args_ty = if args_ty.is_empty() { "unit".to_string() } else { args_ty.join(" -> ") };
+        if sig {
+            wrappers.push(format!("    val {} : {} -> {}", ident, args_ty, output_ty));
+            continue;
+        }
+        let (params, call_args): (Vec<String>, Vec<String>) = if args.is_empty() {
+            (vec!["()".to_string()], vec!["()".to_string()])
+        } else {
+            containers
+                .iter()
+                .enumerate()
+                .map(|(i, container)| match container {
+                    None => (format!("x{}", i), format!("x{}", i)),
+                    Some((ty, to_list, _)) => {
+                        (format!("(x{} : {})", i, ty), format!("({} x{})", to_list, i))
+                    }
+                })
+                .unzip()
+        };
+        let call = format!("{} {}", ident, call_args.join(" "));
+        let body = match output_of_list {
+            None => call,
+            Some(of_list) => format!("{} ({})", of_list, call),
+        };
+        wrappers.push(format!("    let {} {} = {}", ident, params.join(" "), body));
+    }
+    if wrappers.is_empty() {
+        return Ok(());
+    }
+    if sig {
+        writeln!(w, "  module With_base : sig")?;
+        writeln!(w, "{}", wrappers.join("\n"))?;
+        writeln!(w, "  end\n")?;
+    } else {
+        writeln!(w, "  module With_base = struct")?;
+        writeln!(w, "{}", wrappers.join("\n"))?;
+        writeln!(w, "  end;;\n")?;
+    }
+    Ok(())
+}
+
+fn write_header<W: Write>(w: &mut W) -> Result<(), syntax::Error> {
+    // Rust panics are converted to this exception by the generated stubs, the
+    // registered name has to match ocaml_rust::panic::RUST_PANIC_EXN_NAME.
+    writeln!(w, "exception Rust_panic of string;;")?;
+    writeln!(
+        w,
+        "let () = Callback.register_exception \"ocaml_rust.rust_panic\" (Rust_panic \"\");;"
+    )?;
+    // Errors from functions using a rust_error mode, the constructor order has to
+    // match ocaml_rust::error::ErrorKind.
+    writeln!(w, "module Rust_error = struct")?;
+    write!(w, "{}", RUST_ERROR_TYPES)?;
+    writeln!(w, "end")?;
+    writeln!(w)?;
+    writeln!(w, "exception Rust_error of Rust_error.t;;")?;
+    writeln!(
+        w,
+        "let () = Callback.register_exception \"ocaml_rust.rust_error\" (Rust_error {{ Rust_error.kind = Other; message = \"\"; sources = [||] }});;"
+    )?;
+    // Closures returned by Rust are built from a custom block via this function,
+    // the registered names have to match the ones in ocaml_rust::closure.
+    writeln!(w, "type ('a, 'b) rust_fn1 = 'a -> 'b;;")?;
+    writeln!(w, "external _rust_fn1_call : 'f -> 'a -> 'b = \"__ocaml_rust_fn1_call\";;")?;
+    writeln!(
+        w,
+        "let () = Callback.register \"ocaml_rust.rust_fn1\" (fun f x -> _rust_fn1_call f x);;"
+    )?;
+    Ok(())
+}
+
+fn write_sig_header<W: Write>(w: &mut W) -> Result<(), syntax::Error> {
+    writeln!(w, "exception Rust_panic of string")?;
+    writeln!(w)?;
+    writeln!(w, "module Rust_error : sig")?;
+    write!(w, "{}", RUST_ERROR_TYPES)?;
+    writeln!(w, "end")?;
+    writeln!(w)?;
+    writeln!(w, "exception Rust_error of Rust_error.t")?;
+    writeln!(w)?;
+    writeln!(w, "type ('a, 'b) rust_fn1 = 'a -> 'b")?;
+    Ok(())
+}
+
+// The type variables for a generic definition, e.g. "('a, 'b) ".
+fn type_params(generics: &syn::Generics) -> String {
+    let params: Vec<String> = generics
+        .type_params()
+        .map(|param| format!("'{}", syntax::api::ocamlize(&param.ident.to_string())))
+        .collect();
+    match params.len() {
+        0 => String::new(),
+        1 => format!("{} ", params[0]),
+        _ => format!("({}) ", params.join(", ")),
+    }
+}
+
+// Returns the OCaml definition for types declared in the bridge, without the
+// trailing ";;" so that it can be used in both the .ml and .mli files.
+fn type_definition(api_item: &ApiItem) -> Result<Option<String>, syntax::Error> {
+    let mut w = String::new();
+    let doc = |attrs: &[Attribute]| doc_comment(&syntax::api::doc_lines(attrs), "  ");
+    // Doc comments for fields and constructors go after them.
+    let item_doc = |attrs: &[Attribute]| {
+        let lines = syntax::api::doc_lines(attrs);
+        let deprecated = deprecated_attr(syntax::api::deprecated_note(attrs).as_deref(), "@");
+        let deprecated =
+            if deprecated.is_empty() { deprecated } else { format!(" {}", deprecated) };
+        match doc_comment(&lines, "      ") {
+            None => (deprecated, "".to_string()),
+            Some(doc) => (deprecated, format!(" {}", doc.trim_start())),
+        }
+    };
+    match api_item {
+        ApiItem::ForeignMod { .. } | ApiItem::Include(_) | ApiItem::Other(_) => return Ok(None),
+        ApiItem::Enum(e) => {
+            if let Some(doc) = doc(&e.attrs) {
+                w.push_str(&format!("\n{}\n", doc));
+            }
+            w.push_str(&format!(
+                "  type {}{} =\n",
+                type_params(&e.generics),
+                syntax::api::ocamlize(&e.ident.to_string())
+            ));
+            let polymorphic_variant = syntax::api::enum_is_polymorphic_variant(e)?;
+            for (variant_idx, variant) in e.variants.iter().enumerate() {
+                // Polymorphic variants use the Rust spelling as their hash is
+                // computed from it on the Rust side.
+                let variant_ident = if polymorphic_variant {
+                    variant.ident.to_string()
+                } else {
+                    capitalize(&variant.ident.to_string())
+                };
+                let args = match &variant.fields {
+                    syn::Fields::Unit => "".to_string(),
+                    syn::Fields::Unnamed(u) => {
+                        let args: Result<Vec<String>, syntax::Error> = u
+                            .unnamed
+                            .iter()
+                            .map(|x| {
+                                Ok(syntax::api::Type::parse_field_type(&x.ty, &e.generics)?
+                                    .to_ocaml_string())
+                            })
+                            .collect();
+                        let args = args?.join(" * ");
+                        format!(" of {}", args)
                     }
-                    let deriving = ocaml_deriving(&s.attrs);
-                    writeln!(w, "  }} [@@boxed]{};;", deriving)?;
+                    syn::Fields::Named(n) => {
+                        let args: Result<Vec<String>, syntax::Error> = n
+                            .named
+                            .iter()
+                            .map(|x| {
+                                let field_ident = match &x.ident {
+                                    None => {
+                                        let msg = format!(
+                                            "struct with unnamed field {} in enum",
+                                            variant_ident
+                                        );
+                                        return Err(syn::Error::new_spanned(x, msg).into());
+                                    }
+                                    Some(ident) => ident.to_string(),
+                                };
+                                let ty = syntax::api::Type::parse_field_type(&x.ty, &e.generics)?
+                                    .to_ocaml_string();
+                                Ok(format!("{}: {}", field_ident, ty))
+                            })
+                            .collect();
+                        let args = args?.join("; ");
+                        format!(" of {{ {} }}", args)
+                    }
+                };
+                let (deprecated, doc) = item_doc(&variant.attrs);
+                let (sep, variant_ident) = match (polymorphic_variant, variant_idx) {
+                    (false, _) => ("|", variant_ident),
+                    (true, 0) => ("[", format!("`{}", variant_ident)),
+                    (true, _) => ("|", format!("`{}", variant_ident)),
+                };
+                w.push_str(&format!("  {} {}{}{}{}\n", sep, variant_ident, args, deprecated, doc));
+            }
+            let deriving = ocaml_deriving(&e.attrs);
+            let deprecated =
+                deprecated_attr(syntax::api::deprecated_note(&e.attrs).as_deref(), "@@");
+            if polymorphic_variant {
+                let attrs = format!("{}{}", deriving, deprecated);
+                // The opening bracket comes with the first constructor.
+                let close = if e.variants.is_empty() { "[ ]" } else { "]" };
+                if attrs.is_empty() {
+                    w.push_str(&format!("  {}", close));
+                } else {
+                    w.push_str(&format!("  {} {}", close, attrs));
                 }
-                ApiItem::Type(i) => {
-                    writeln!(w, "  type {};;", syntax::api::ocamlize(&i.ident.to_string()))?;
+            } else {
+                w.push_str(&format!("  [@@boxed]{}{}", deriving, deprecated));
+            }
+        }
+        ApiItem::Struct(s) => {
+            if let Some(doc) = doc(&s.attrs) {
+                w.push_str(&format!("\n{}\n", doc));
+            }
+            let ocaml_ident = format!(
+                "{}{}",
+                type_params(&s.generics),
+                syntax::api::ocamlize(&s.ident.to_string())
+            );
+            let deriving = ocaml_deriving(&s.attrs);
+            let deprecated =
+                deprecated_attr(syntax::api::deprecated_note(&s.attrs).as_deref(), "@@");
+            // Unit structs map to unit and tuple structs to a single constructor.
+            if s.fields.is_empty() {
+                w.push_str(&format!("  type {} = unit{}{}", ocaml_ident, deriving, deprecated));
+                return Ok(Some(w));
+            }
+            if let syn::Fields::Unnamed(fields) = &s.fields {
+                let args: Result<Vec<String>, syntax::Error> = fields
+                    .unnamed
+                    .iter()
+                    .map(|x| {
+                        Ok(syntax::api::Type::parse_field_type(&x.ty, &s.generics)?
+                            .to_ocaml_string())
+                    })
+                    .collect();
+                let boxed =
+                    if syntax::api::struct_is_unboxed(s)? { "[@@unboxed]" } else { "[@@boxed]" };
+                w.push_str(&format!(
+                    "  type {} = {} of {} {}{}{}",
+                    ocaml_ident,
+                    capitalize(&s.ident.to_string()),
+                    args?.join(" * "),
+                    boxed,
+                    deriving,
+                    deprecated
+                ));
+                return Ok(Some(w));
+            }
+            w.push_str(&format!("  type {} = {{\n", ocaml_ident));
+            for field in s.fields.iter() {
+                let ident = match &field.ident {
+                    None => {
+                        return Err(syn::Error::new_spanned(
+                            field,
+                            format!("struct with unnamed field {}", s.ident),
+                        )
+                        .into())
+                    }
+                    Some(ident) => ident,
+                };
+                let ty =
+                    syntax::api::Type::parse_field_type(&field.ty, &s.generics)?.to_ocaml_string();
+                let (deprecated, doc) = item_doc(&field.attrs);
+                w.push_str(&format!("    {}: {}{};{}\n", ident, ty, deprecated, doc));
+            }
+            w.push_str(&format!("  }} [@@boxed]{}{}", deriving, deprecated));
+        }
+        ApiItem::Type(i) => {
+            if let Some(doc) = doc(&i.attrs) {
+                w.push_str(&format!("\n{}\n", doc));
+            }
+            let deprecated =
+                deprecated_attr(syntax::api::deprecated_note(&i.attrs).as_deref(), "@@");
+            w.push_str(&format!(
+                "  type {}{}",
+                syntax::api::ocamlize(&i.ident.to_string()),
+                deprecated
+            ));
+        }
+    }
+    Ok(Some(w))
+}
+
+fn has_deprecated(api: &syntax::api::Api) -> bool {
+    let deprecated = |attrs: &[Attribute]| syntax::api::deprecated_note(attrs).is_some();
+    api.api_items.iter().any(|api_item| match api_item {
+        ApiItem::ForeignMod { items, .. } => items.iter().any(|item| match item {
+            ModItem::Fn { attrs, .. } => attrs.deprecated.is_some(),
+        }),
+        ApiItem::Enum(e) => deprecated(&e.attrs) || e.variants.iter().any(|v| deprecated(&v.attrs)),
+        ApiItem::Struct(s) => deprecated(&s.attrs) || s.fields.iter().any(|f| deprecated(&f.attrs)),
+        ApiItem::Type(i) => deprecated(&i.attrs),
+        ApiItem::Include(_) | ApiItem::Other(_) => false,
+    })
+}
+
+fn write_layout_check<W: Write>(
+    w: &mut W,
+    indent: &str,
+    fn_name: &str,
+    fingerprint: &str,
+    what: &str,
+) -> Result<(), syntax::Error> {
+    writeln!(w, "{}external _layout_fingerprint : unit -> string = \"{}\";;", indent, fn_name)?;
+    writeln!(w, "{}let () =", indent)?;
+    writeln!(w, "{}  let fingerprint = _layout_fingerprint () in", indent)?;
+    writeln!(w, "{}  if not (String.equal fingerprint \"{}\") then", indent, fingerprint)?;
+    writeln!(
+        w,
+        "{}    failwith (\"ocaml-rust: the layout of {} differs between OCaml ({}) and Rust (\" ^ fingerprint ^ \"), the bindings have to be regenerated\");;",
+        indent, what, fingerprint
+    )?;
+    Ok(())
+}
+
+// Checks that all the toplevel items of an included snippet can appear in a
+// signature as is.
+fn include_is_sig(include: &str) -> bool {
+    include.lines().filter(|l| !l.starts_with(char::is_whitespace)).all(|l| {
+        let keyword = l.split_whitespace().next().unwrap_or_default();
+        matches!(keyword, "" | "open" | "open!" | "type" | "exception") || keyword.starts_with("(*")
+    })
+}
+
+fn write_api<W: Write>(
+    w: &mut W,
+    api: &syntax::api::Api,
+    sig: bool,
+    base_helpers: bool,
+) -> Result<(), syntax::Error> {
+    if sig {
+        writeln!(w, "\nmodule {} : sig", capitalize(&api.ident.to_string()))?;
+    } else {
+        writeln!(w, "module {} = struct", capitalize(&api.ident.to_string()))?;
+    }
+    // Deprecated items can be referred to by the generated code itself.
+    if has_deprecated(api) {
+        writeln!(w, "  [@@@ocaml.alert \"-deprecated\"]")?;
+        if sig {
+            writeln!(w)?;
+        }
+    }
+    for api_item in api.api_items.iter() {
+        match api_item {
+            ApiItem::Include(include) if !sig => writeln!(w, "{}", include)?,
+            // Includes are copied to the interface so that the types they define
+            // can be used, this is only possible for items that are also valid
+            // in a signature.
+            ApiItem::Include(include) => {
+                if !include_is_sig(include) {
+                    let msg = "ocaml_include! can only contain open, type and exception items when generating an mli file";
+                    return Err(syn::Error::new(api.ident.span(), msg).into());
                 }
-                ApiItem::Include(include) => {
-                    writeln!(w, "{}", include)?;
+                writeln!(w, "{}\n", include)?
+            }
+            api_item => match type_definition(api_item)? {
+                Some(def) if sig => writeln!(w, "{}\n", def.trim_start_matches('\n'))?,
+                Some(def) => writeln!(w, "{};;", def)?,
+                None => {}
+            },
+        }
+    }
+    // Fail when loading the module if the Rust types or signatures have
+    // changed since the bindings were generated.
+    if let (false, Some(fingerprint)) = (sig, api.layout_fingerprint()) {
+        let fn_name = crate::syntax::expand::layout_fn_name(&api.ident);
+        let what = format!("the types in {}", capitalize(&api.ident.to_string()));
+        write_layout_check(w, "  ", &fn_name, &fingerprint, &what)?;
+    }
+    let mut in_scope = InScope::new();
+    let mut ocaml_items = vec![];
+    for api_item in api.api_items.iter() {
+        match api_item {
+            ApiItem::ForeignMod { lang: Lang::Rust, items, .. } => {
+                for item in items {
+                    in_scope.insert(item)
                 }
-                ApiItem::Other(_) => {}
             }
+            ApiItem::ForeignMod { lang: Lang::OCaml, items, .. } => ocaml_items.extend(items),
+            ApiItem::Include(_)
+            | ApiItem::Enum(_)
+            | ApiItem::Struct(_)
+            | ApiItem::Type(_)
+            | ApiItem::Other(_) => {}
         }
-        let mut in_scope = InScope::new();
-        for api_item in api.api_items.into_iter() {
-            match api_item {
-                ApiItem::ForeignMod { lang: Lang::Rust, items, .. } => {
-                    for item in items {
-                        in_scope.insert(item)
-                    }
+    }
+    in_scope.write(w, &api.ident, sig, base_helpers)?;
+    write_registers(w, &ocaml_items, &api.ident, sig)?;
+    writeln!(w, "end")?;
+    Ok(())
+}
+
+// The crate name as used by rustc, i.e. the lib name or the package name with
+// dashes replaced by underscores, read from the closest Cargo.toml.
+fn find_crate_name(rust_file: &str) -> Option<String> {
+    let rust_file = std::fs::canonicalize(rust_file).ok()?;
+    let manifest = rust_file.ancestors().map(|dir| dir.join("Cargo.toml")).find(|p| p.is_file())?;
+    let manifest = std::fs::read_to_string(manifest).ok()?;
+    let mut section = "";
+    let mut package_name = None;
+    let mut lib_name = None;
+    for line in manifest.lines().map(str::trim) {
+        if line.starts_with('[') {
+            section = line;
+        } else if let Some(value) =
+            line.strip_prefix("name").and_then(|l| l.trim().strip_prefix('='))
+        {
+            let value = value.trim().trim_matches('"').to_string();
+            match section {
+                "[package]" => package_name = Some(value),
+                "[lib]" => lib_name = Some(value),
+                _ => {}
+            }
+        }
+    }
+    lib_name.or(package_name).map(|name| name.replace('-', "_"))
+}
+
+// Types deriving ToValue or FromValue outside of a bridge are defined at the
+// top level so that all the bridge modules can refer to them.
+fn write_derived<W: Write>(
+    w: &mut W,
+    derived: &[ApiItem],
+    crate_name: &str,
+    sig: bool,
+) -> Result<(), syntax::Error> {
+    for api_item in derived.iter() {
+        if let Some(def) = type_definition(api_item)? {
+            let def: Vec<&str> =
+                def.split('\n').map(|l| l.strip_prefix("  ").unwrap_or(l)).collect();
+            let def = def.join("\n");
+            if sig {
+                writeln!(w, "\n{}", def.trim_start_matches('\n'))?;
+            } else {
+                writeln!(w, "{};;", def)?;
+                let (ident, attrs) = match api_item {
+                    ApiItem::Struct(s) => (&s.ident, &s.attrs),
+                    ApiItem::Enum(e) => (&e.ident, &e.attrs),
+                    _ => continue,
+                };
+                if let Some(fingerprint) =
+                    crate::syntax::expand::derived_layout_fingerprint(api_item)
+                {
+                    let to_value = crate::syntax::file::derives_to_value(attrs);
+                    let fn_name =
+                        crate::syntax::expand::derived_layout_fn_name(crate_name, ident, to_value);
+                    let what = format!("type {}", syntax::api::ocamlize(&ident.to_string()));
+                    write_layout_check(w, "", &fn_name, &fingerprint, &what)?;
                 }
-                ApiItem::ForeignMod { lang: Lang::OCaml, .. }
-                | ApiItem::Include(_)
-                | ApiItem::Enum(_)
-                | ApiItem::Struct(_)
-                | ApiItem::Type(_)
-                | ApiItem::Other(_) => {}
             }
         }
-        in_scope.write(&mut w, &api.ident)?;
-        writeln!(w, "end")?;
+    }
+    Ok(())
+}
+
+fn try_main(args: Args) -> Result<(), syntax::Error> {
+    let rust_source = read_to_string(&args.rust_file)?;
+    proc_macro2::fallback::force();
+    let file: File = syn::parse_str(&rust_source)?;
+    let crate_name = match args.crate_name.or_else(|| find_crate_name(&args.rust_file)) {
+        Some(crate_name) => crate_name,
+        None if file.derived.is_empty() => String::new(),
+        None => {
+            let msg = "cannot find the crate name for the derived types, use --crate-name";
+            return Err(std::io::Error::new(std::io::ErrorKind::NotFound, msg).into());
+        }
+    };
+    // Derived types share the top level namespace on the OCaml side.
+    let mut derived_idents = std::collections::HashSet::new();
+    for api_item in file.derived.iter() {
+        let ident = match api_item {
+            ApiItem::Struct(s) => &s.ident,
+            ApiItem::Enum(e) => &e.ident,
+            _ => continue,
+        };
+        if !derived_idents.insert(ident.to_string()) {
+            let msg = format!(
+                "{} is derived more than once, derived types must have distinct names",
+                ident
+            );
+            return Err(syn::Error::new(ident.span(), msg).into());
+        }
+    }
+    let mut w = std::fs::File::create(args.ocaml_file)?;
+    write_header(&mut w)?;
+    if args.base_helpers {
+        write!(w, "{}", BASE_HELPERS)?;
+    }
+    write_derived(&mut w, &file.derived, &crate_name, false)?;
+    for api in file.apis.iter() {
+        write_api(&mut w, api, false, args.base_helpers)?;
+    }
+    if let Some(mli_file) = args.mli_file {
+        let mut w = std::fs::File::create(mli_file)?;
+        write_sig_header(&mut w)?;
+        if args.base_helpers {
+            write!(w, "\n{}", BASE_HELPERS_SIG)?;
+        }
+        write_derived(&mut w, &file.derived, &crate_name, true)?;
+        for api in file.apis.iter() {
+            write_api(&mut w, api, true, args.base_helpers)?;
+        }
     }
     Ok(())
 }
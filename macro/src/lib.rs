@@ -8,3 +8,52 @@ pub fn bridge(_args: TokenStream, input: TokenStream) -> TokenStream {
     let api = parse_macro_input!(input as syntax::api::Api);
     api.expand().unwrap_or_else(|err| err.to_compile_error()).into()
 }
+
+/// Derives the conversion to OCaml values for a struct or enum declared outside
+/// of a bridge module, the layout is the same as for types declared in a bridge.
+#[proc_macro_derive(ToValue, attributes(ocaml, ocaml_deriving, ocaml_unboxed))]
+pub fn derive_to_value(input: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(input as syn::Item);
+    let mut expanded = proc_macro2::TokenStream::new();
+    let res = match &item {
+        syn::Item::Struct(item) => {
+            let api_item = syntax::api::ApiItem::Struct(item.clone());
+            syntax::expand::derived_layout_stub(&api_item, &item.ident, true, &mut expanded);
+            syntax::expand::struct_to_value(item, &mut expanded)
+        }
+        syn::Item::Enum(item) => {
+            let api_item = syntax::api::ApiItem::Enum(item.clone());
+            syntax::expand::derived_layout_stub(&api_item, &item.ident, true, &mut expanded);
+            syntax::expand::enum_to_value(item, &mut expanded)
+        }
+        item => {
+            Err(syn::Error::new_spanned(item, "ToValue can only be derived for structs and enums"))
+        }
+    };
+    res.map(|()| expanded).unwrap_or_else(|err| err.to_compile_error()).into()
+}
+
+/// Derives the conversion from OCaml values for a struct or enum declared
+/// outside of a bridge module.
+#[proc_macro_derive(FromValue, attributes(ocaml, ocaml_deriving, ocaml_unboxed))]
+pub fn derive_from_value(input: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(input as syn::Item);
+    let mut expanded = proc_macro2::TokenStream::new();
+    let res = match &item {
+        syn::Item::Struct(item) => {
+            let api_item = syntax::api::ApiItem::Struct(item.clone());
+            syntax::expand::derived_layout_stub(&api_item, &item.ident, false, &mut expanded);
+            syntax::expand::struct_from_value(item, &mut expanded)
+        }
+        syn::Item::Enum(item) => {
+            let api_item = syntax::api::ApiItem::Enum(item.clone());
+            syntax::expand::derived_layout_stub(&api_item, &item.ident, false, &mut expanded);
+            syntax::expand::enum_from_value(item, &mut expanded)
+        }
+        item => Err(syn::Error::new_spanned(
+            item,
+            "FromValue can only be derived for structs and enums",
+        )),
+    };
+    res.map(|()| expanded).unwrap_or_else(|err| err.to_compile_error()).into()
+}
@@ -1,78 +1,478 @@
+//! OCaml bigarrays with a static number of dimensions, `BigArray1`, `BigArray2`
+//! and `BigArray3`, or a dynamic one with `Genarray`. The layout is a type
+//! parameter defaulting to `CLayout`, `FortranLayout` uses column-major order.
+//!
+//! The content is exposed as a flat slice together with the shape and the
+//! strides expressed in number of elements. With the `ndarray` feature, `view`
+//! and `view_mut` wrap it in an `ndarray` view without copying.
+//!
+//! Bigarray data lives outside of the OCaml heap and is never moved by the GC,
+//! the data pointer and dimensions are read when the value is converted so
+//! accessing the content does not require the OCaml runtime lock. The slices
+//! can be used after releasing it and are `Send`, e.g. to process them with
+//! rayon. The bigarray values themselves have to be dropped with the lock held,
+//! so functions with `#[release_runtime_lock]` take them by reference and the
+//! generated code raises `Invalid_argument` when a bigarray taken by value or
+//! mutably borrowed overlaps with another bigarray argument.
 use crate::rooted::RootedValue;
 use crate::to_value::ToValue;
-use ocaml_sys::bigarray::Kind;
+use ocaml_sys::bigarray::Bigarray;
+use std::marker::PhantomData;
 
+#[cfg(feature = "float16")]
+pub use half::f16;
+#[cfg(feature = "complex")]
+pub use num_complex::{Complex32, Complex64};
+
+/// The element kinds from bigarray.h, FLOAT16 was added in OCaml 5.2 and is
+/// missing from ocaml_sys.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    FLOAT32 = 0x00,
+    FLOAT64 = 0x01,
+    SINT8 = 0x02,
+    UINT8 = 0x03,
+    SINT16 = 0x04,
+    UINT16 = 0x05,
+    INT32 = 0x06,
+    INT64 = 0x07,
+    CAML_INT = 0x08,
+    NATIVE_INT = 0x09,
+    COMPLEX32 = 0x0a,
+    COMPLEX64 = 0x0b,
+    CHAR = 0x0c,
+    FLOAT16 = 0x0d,
+}
+
+const KIND_MASK: ocaml_sys::Intnat = 0xFF;
+
+/// Element types that can be stored in a bigarray, these must have the same
+/// memory representation as the corresponding OCaml kind.
 pub trait Elem {
     const KIND: Kind;
 }
 
-pub struct BigArray1<E: 'static>(RootedValue<Vec<E>>);
+/// An element of a `nativeint` bigarray, `isize` maps to the `int` kind.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NativeInt(pub isize);
+
+/// An element of a `char` bigarray, a Rust `char` uses four bytes.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OCamlChar(pub u8);
+
+// https://github.com/ocaml/ocaml/blob/66b63e2f2459c0a2754658e847894eacb4cacc34/runtime/caml/bigarray.h#L61
+const LAYOUT_MASK: ocaml_sys::Intnat = 0x100;
+
+pub trait Layout {
+    const FLAG: i32;
+}
+
+/// Row-major layout, indexes start at 0 on the OCaml side.
+pub enum CLayout {}
+
+/// Column-major layout, indexes start at 1 on the OCaml side. The Rust
+/// accessors always use 0-based indexes.
+pub enum FortranLayout {}
+
+impl Layout for CLayout {
+    const FLAG: i32 = 0;
+}
+
+impl Layout for FortranLayout {
+    const FLAG: i32 = 0x100;
+}
+
+extern "C" {
+    // The binding in ocaml_sys uses the wrong type for dim.
+    fn caml_ba_alloc(
+        flags: i32,
+        num_dims: i32,
+        data: ocaml_sys::bigarray::Data,
+        dim: *const ocaml_sys::Intnat,
+    ) -> ocaml_sys::Value;
+}
+
+unsafe fn bigarray<'a>(v: ocaml_sys::Value) -> &'a Bigarray {
+    &*(ocaml_sys::field(v, 1) as *const Bigarray)
+}
+
+unsafe fn shape(v: ocaml_sys::Value) -> Vec<usize> {
+    let ba = bigarray(v);
+    let dim = std::slice::from_raw_parts(ba.dim.as_ptr(), ba.num_dims as usize);
+    dim.iter().map(|&d| d as usize).collect()
+}
 
-impl<E: Elem> crate::from_value::FromSysValue for BigArray1<E> {
-    unsafe fn from_value(v: ocaml_sys::Value) -> Self {
-        BigArray1(RootedValue::create(v))
+unsafe fn check<E: Elem, L: Layout>(v: ocaml_sys::Value, num_dims: Option<usize>) {
+    let ba = bigarray(v);
+    if let Some(num_dims) = num_dims {
+        if ba.num_dims as usize != num_dims {
+            crate::panic::invalid_argument(format!(
+                "unexpected number of dimensions for bigarray {}",
+                ba.num_dims
+            ))
+        }
+    }
+    let kind = ba.flags & KIND_MASK;
+    if kind != E::KIND as ocaml_sys::Intnat {
+        crate::panic::invalid_argument(format!("unexpected kind for bigarray {kind}"))
+    }
+    if ba.flags & LAYOUT_MASK != L::FLAG as ocaml_sys::Intnat {
+        let layout = ba.flags & LAYOUT_MASK;
+        crate::panic::invalid_argument(format!("unexpected layout for bigarray {layout}"))
     }
 }
 
-impl<E: Elem> ToValue for BigArray1<E> {
-    fn to_value(&self) -> ocaml_sys::Value {
-        self.0.value().value
+// The data is allocated by OCaml so that it is freed by the GC.
+fn alloc<E: Elem, L: Layout>(vs: &[E], shape: &[usize]) -> ocaml_sys::Value {
+    let len: usize = shape.iter().product();
+    if len != vs.len() {
+        panic!("bigarray shape {:?} does not match the number of elements {}", shape, vs.len())
+    }
+    crate::borrowed::check_alloc();
+    let dim: Vec<ocaml_sys::Intnat> = shape.iter().map(|&d| d as ocaml_sys::Intnat).collect();
+    let flags = E::KIND as i32 | L::FLAG;
+    unsafe {
+        let value = caml_ba_alloc(flags, dim.len() as i32, std::ptr::null_mut(), dim.as_ptr());
+        std::ptr::copy_nonoverlapping(vs.as_ptr(), bigarray(value).data as *mut E, len);
+        value
     }
 }
 
-impl<E: Elem> BigArray1<E> {
-    pub fn new(vs: &[E]) -> Self {
-        // https://github.com/ocaml/ocaml/blob/66b63e2f2459c0a2754658e847894eacb4cacc34/runtime/caml/bigarray.h#L61
-        // CAML_BA_C_LAYOUT is 0
-        let flags = E::KIND as i32;
-        let len = vs.len();
-        unsafe {
-            let content = ocaml_sys::bigarray::malloc(len * std::mem::size_of::<E>());
-            std::ptr::copy_nonoverlapping(vs.as_ptr(), content as *mut E, len);
-            let value = ocaml_sys::bigarray::caml_ba_alloc_dims(flags, 1, content, len);
-            BigArray1(RootedValue::create(value))
+extern "C" {
+    static mut caml_ba_ops: ocaml_sys::custom_operations;
+}
+
+const MANAGED_MASK: ocaml_sys::Intnat = 0x600;
+const MANAGED: ocaml_sys::Intnat = 0x200;
+
+// Bigarrays wrapping Rust owned data are flagged as managed and get a proxy
+// from the start, the runtime then shares it with the sub-arrays, slices and
+// reshaped views, incrementing the reference count. The runtime only sets the
+// size field for mapped files so a non-null owner marks the proxies created
+// here. The layout matches struct caml_ba_proxy which is the same in OCaml 4.14
+// and 5.x: a reference count, the data pointer and the mapped size, each one
+// word. The assertions below check this against the ocaml-sys definition.
+#[repr(C)]
+struct OwnerProxy {
+    refcount: std::sync::atomic::AtomicIsize,
+    data: ocaml_sys::bigarray::Data,
+    owner: *mut Box<dyn std::any::Any + Send>,
+}
+
+const _: () = {
+    use std::mem::{offset_of, size_of};
+    let word = size_of::<ocaml_sys::Intnat>();
+    assert!(size_of::<OwnerProxy>() == size_of::<ocaml_sys::bigarray::BigarrayProxy>());
+    assert!(size_of::<OwnerProxy>() == 3 * word);
+    assert!(offset_of!(OwnerProxy, refcount) == 0);
+    assert!(offset_of!(OwnerProxy, data) == word);
+    assert!(offset_of!(OwnerProxy, owner) == 2 * word);
+};
+
+static CAML_BA_FINALIZE: std::sync::OnceLock<Option<unsafe extern "C" fn(ocaml_sys::Value)>> =
+    std::sync::OnceLock::new();
+
+// The runtime finalizer would free the data of a managed bigarray once the
+// reference count drops to zero, this is replaced by dropping the owner. This
+// runs during a collection so the owner must not use the OCaml runtime when
+// dropped, and a panic is caught as unwinding into the runtime would abort.
+unsafe extern "C" fn finalize(v: ocaml_sys::Value) {
+    let ba = bigarray(v);
+    if ba.flags & MANAGED_MASK == MANAGED && !ba.proxy.is_null() {
+        let proxy = ba.proxy as *mut OwnerProxy;
+        if !(*proxy).owner.is_null() {
+            if (*proxy).refcount.fetch_sub(1, std::sync::atomic::Ordering::AcqRel) == 1 {
+                let proxy = Box::from_raw(proxy);
+                let owner = Box::from_raw(proxy.owner);
+                let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| drop(owner)));
+            }
+            return;
         }
     }
+    if let Some(Some(finalize)) = CAML_BA_FINALIZE.get() {
+        finalize(v)
+    }
+}
+
+// Sub-arrays are allocated by the runtime with caml_ba_ops so the finalizer is
+// replaced there rather than in a copy of the custom operations. This is a
+// global of the runtime, the OnceLock ensures that it is patched a single time,
+// with the runtime lock held as this is only called when allocating, and before
+// any bigarray using the new finalizer exists. Bigarrays allocated earlier are
+// finalized by the original function which the replacement falls back to.
+fn install_finalizer() {
+    CAML_BA_FINALIZE.get_or_init(|| unsafe {
+        let ops = std::ptr::addr_of_mut!(caml_ba_ops);
+        let previous = (*ops).finalize;
+        (*ops).finalize = Some(finalize);
+        previous
+    });
+}
 
-    pub fn data(&self) -> &[E] {
-        let v = self.0.to_value();
-        let ba = unsafe { ocaml_sys::field(v, 1) } as *mut ocaml_sys::bigarray::Bigarray;
-        let ba = unsafe { &*ba };
-        if ba.num_dims != 1 {
-            panic!("unexpected number of dimensions for bigarray {}", ba.num_dims)
+/// # Safety
+/// `data` must point to `shape.iter().product()` elements that stay valid until
+/// `owner` is dropped.
+unsafe fn alloc_owned<E: Elem, L: Layout, O: Send + 'static>(
+    data: *mut E,
+    shape: &[usize],
+    owner: O,
+) -> ocaml_sys::Value {
+    crate::borrowed::check_alloc();
+    install_finalizer();
+    let dim: Vec<ocaml_sys::Intnat> = shape.iter().map(|&d| d as ocaml_sys::Intnat).collect();
+    let flags = E::KIND as i32 | L::FLAG | MANAGED as i32;
+    let value =
+        caml_ba_alloc(flags, dim.len() as i32, data as ocaml_sys::bigarray::Data, dim.as_ptr());
+    let owner: Box<Box<dyn std::any::Any + Send>> = Box::new(Box::new(owner));
+    let proxy = OwnerProxy {
+        refcount: std::sync::atomic::AtomicIsize::new(1),
+        data: data as ocaml_sys::bigarray::Data,
+        owner: Box::into_raw(owner),
+    };
+    let ba = ocaml_sys::field(value, 1) as *mut Bigarray;
+    (*ba).proxy = Box::into_raw(Box::new(proxy)) as *const _;
+    value
+}
+
+fn strides<L: Layout>(shape: &[usize]) -> Vec<usize> {
+    let mut strides = vec![0; shape.len()];
+    let mut stride = 1;
+    if L::FLAG == FortranLayout::FLAG {
+        for (s, d) in strides.iter_mut().zip(shape.iter()) {
+            *s = stride;
+            stride *= d;
+        }
+    } else {
+        for (s, d) in strides.iter_mut().zip(shape.iter()).rev() {
+            *s = stride;
+            stride *= d;
         }
-        let dim = unsafe { std::slice::from_raw_parts(ba.dim.as_ptr() as *const isize, 1) };
-        unsafe { std::slice::from_raw_parts(ba.data as *const E, dim[0] as usize) }
     }
+    strides
+}
 
-    pub fn data_mut(&mut self) -> &mut [E] {
-        let v = self.0.to_value();
-        let ba = unsafe { ocaml_sys::field(v, 1) } as *mut ocaml_sys::bigarray::Bigarray;
-        let ba = unsafe { &*ba };
-        if ba.num_dims != 1 {
-            panic!("unexpected number of dimensions for bigarray {}", ba.num_dims)
+macro_rules! bigarray {
+    ($name:ident, $num_dims:expr, $index:ty, $dim:ty) => {
+        pub struct $name<E: 'static, L: 'static = CLayout> {
+            root: RootedValue<Vec<E>>,
+            data: *mut E,
+            shape: Vec<usize>,
+            phantom: PhantomData<L>,
         }
-        let dim = unsafe { std::slice::from_raw_parts(ba.dim.as_ptr() as *const isize, 1) };
-        unsafe { std::slice::from_raw_parts_mut(ba.data as *mut E, dim[0] as usize) }
-    }
+
+        impl<E: Elem, L: Layout> crate::from_value::FromSysValue for $name<E, L> {
+            unsafe fn from_value(v: ocaml_sys::Value) -> Self {
+                check::<E, L>(v, $num_dims);
+                Self::wrap(v)
+            }
+        }
+
+        impl<E: Elem, L: Layout> ToValue for $name<E, L> {
+            fn to_value(&self) -> ocaml_sys::Value {
+                self.root.value().value
+            }
+        }
+
+        impl<E: Elem, L: Layout> $name<E, L> {
+            // The data pointer and the dimensions of a bigarray never change, so they
+            // are read once here and the accessors do not touch the OCaml heap.
+            unsafe fn wrap(v: ocaml_sys::Value) -> Self {
+                let data = bigarray(v).data as *mut E;
+                $name { root: RootedValue::create(v), data, shape: shape(v), phantom: PhantomData }
+            }
+
+            pub fn shape(&self) -> Vec<usize> {
+                self.shape.clone()
+            }
+
+            /// The distance between two consecutive elements along each dimension,
+            /// expressed in number of elements.
+            pub fn strides(&self) -> Vec<usize> {
+                strides::<L>(&self.shape)
+            }
+
+            pub fn is_fortran_layout(&self) -> bool {
+                L::FLAG == FortranLayout::FLAG
+            }
+
+            /// The content in memory order.
+            pub fn data(&self) -> &[E] {
+                // The data pointer of an empty bigarray can be NULL.
+                let len = self.shape.iter().product();
+                if len == 0 {
+                    return &[];
+                }
+                unsafe { std::slice::from_raw_parts(self.data, len) }
+            }
+
+            pub fn data_mut(&mut self) -> &mut [E] {
+                let len = self.shape.iter().product();
+                if len == 0 {
+                    return &mut [];
+                }
+                unsafe { std::slice::from_raw_parts_mut(self.data, len) }
+            }
+
+            /// An `ndarray` view of the content, the strides follow the layout.
+            #[cfg(feature = "ndarray")]
+            pub fn view(&self) -> ndarray::ArrayView<'_, E, $dim> {
+                use ndarray::ShapeBuilder;
+                let shape = ndarray::IxDyn(&self.shape).strides(ndarray::IxDyn(&self.strides()));
+                ndarray::ArrayView::from_shape(shape, self.data())
+                    .and_then(|view| view.into_dimensionality())
+                    .expect("bigarray shape is not compatible with its data")
+            }
+
+            #[cfg(feature = "ndarray")]
+            pub fn view_mut(&mut self) -> ndarray::ArrayViewMut<'_, E, $dim> {
+                use ndarray::ShapeBuilder;
+                let shape = ndarray::IxDyn(&self.shape).strides(ndarray::IxDyn(&self.strides()));
+                ndarray::ArrayViewMut::from_shape(shape, self.data_mut())
+                    .and_then(|view| view.into_dimensionality())
+                    .expect("bigarray shape is not compatible with its data")
+            }
+
+            /// The address range of the content, used to check that a bigarray
+            /// borrowed mutably does not overlap with another argument.
+            #[doc(hidden)]
+            pub fn data_range(&self) -> std::ops::Range<usize> {
+                let start = self.data as usize;
+                start..start + std::mem::size_of_val(self.data())
+            }
+
+            fn offset(&self, index: $index) -> Option<usize> {
+                let index: &[usize] = index.as_ref();
+                let shape = &self.shape;
+                if index.len() != shape.len() || index.iter().zip(shape.iter()).any(|(i, d)| i >= d)
+                {
+                    return None;
+                }
+                Some(index.iter().zip(strides::<L>(shape).iter()).map(|(i, s)| i * s).sum())
+            }
+
+            /// Wraps data owned by Rust, e.g. a `Vec<E>` or `Box<[E]>`, without copying it.
+            /// OCaml can write to the data so the owner has to be unique. It is dropped
+            /// once OCaml has finalized the bigarray and the sub-arrays, slices and
+            /// reshaped views created from it. This happens during a collection so
+            /// dropping the owner must not use the OCaml runtime, panics are ignored.
+            pub fn from_owner<O: AsMut<[E]> + Send + 'static>(owner: O, shape: $index) -> Self {
+                let shape: &[usize] = shape.as_ref();
+                // Box the owner first so that the data does not move for owners
+                // storing it inline.
+                let mut owner = Box::new(owner);
+                let data = (*owner).as_mut();
+                let len: usize = shape.iter().product();
+                if len != data.len() {
+                    panic!(
+                        "bigarray shape {:?} does not match the number of elements {}",
+                        shape,
+                        data.len()
+                    )
+                }
+                let data = data.as_mut_ptr();
+                unsafe { Self::wrap(alloc_owned::<E, L, Box<O>>(data, shape, owner)) }
+            }
+
+            /// Wraps external data without copying it, `owner` is dropped once OCaml
+            /// has finalized the bigarray and the views created from it, e.g. a
+            /// memory-mapped file, an `Arc<[E]>` or `()` for static data. As for
+            /// `from_owner`, dropping the owner must not use the OCaml runtime.
+            ///
+            /// # Safety
+            /// `data` must point to the number of elements given by `shape` and stay
+            /// valid until `owner` is dropped. When the data is shared, e.g. with an
+            /// `Arc<[E]>`, or read-only, neither OCaml nor Rust must write to the
+            /// bigarray.
+            pub unsafe fn from_raw_parts<O: Send + 'static>(
+                data: *mut E,
+                shape: $index,
+                owner: O,
+            ) -> Self {
+                Self::wrap(alloc_owned::<E, L, O>(data, shape.as_ref(), owner))
+            }
+
+            /// Returns the element at the given 0-based index, or `None` if the index
+            /// is out of bounds.
+            pub fn get(&self, index: $index) -> Option<&E> {
+                let offset = self.offset(index)?;
+                Some(&self.data()[offset])
+            }
+
+            pub fn get_mut(&mut self, index: $index) -> Option<&mut E> {
+                let offset = self.offset(index)?;
+                Some(&mut self.data_mut()[offset])
+            }
+        }
+    };
+}
+
+bigarray!(BigArray1, Some(1), [usize; 1], ndarray::Ix1);
+bigarray!(BigArray2, Some(2), [usize; 2], ndarray::Ix2);
+bigarray!(BigArray3, Some(3), [usize; 3], ndarray::Ix3);
+bigarray!(Genarray, None, &[usize], ndarray::IxDyn);
+
+/// Returns true when the two address ranges overlap, empty ranges never do.
+#[doc(hidden)]
+pub fn overlap(r1: std::ops::Range<usize>, r2: std::ops::Range<usize>) -> bool {
+    r1.start < r2.end && r2.start < r1.end
 }
 
-impl Elem for u8 {
-    const KIND: Kind = Kind::UINT8;
+impl<E: Elem, L: Layout> BigArray1<E, L> {
+    pub fn new(vs: &[E]) -> Self {
+        unsafe { Self::wrap(alloc::<E, L>(vs, &[vs.len()])) }
+    }
 }
 
-impl Elem for f32 {
-    const KIND: Kind = Kind::FLOAT32;
+impl<E: Elem, L: Layout> BigArray2<E, L> {
+    /// Creates a bigarray from elements given in the memory order of the layout.
+    pub fn new(vs: &[E], shape: [usize; 2]) -> Self {
+        unsafe { Self::wrap(alloc::<E, L>(vs, &shape)) }
+    }
 }
 
-impl Elem for f64 {
-    const KIND: Kind = Kind::FLOAT64;
+impl<E: Elem, L: Layout> BigArray3<E, L> {
+    /// Creates a bigarray from elements given in the memory order of the layout.
+    pub fn new(vs: &[E], shape: [usize; 3]) -> Self {
+        unsafe { Self::wrap(alloc::<E, L>(vs, &shape)) }
+    }
 }
 
-impl Elem for i32 {
-    const KIND: Kind = Kind::INT32;
+impl<E: Elem, L: Layout> Genarray<E, L> {
+    /// Creates a bigarray from elements given in the memory order of the layout.
+    pub fn new(vs: &[E], shape: &[usize]) -> Self {
+        unsafe { Self::wrap(alloc::<E, L>(vs, shape)) }
+    }
+
+    pub fn num_dims(&self) -> usize {
+        self.shape.len()
+    }
 }
 
-impl Elem for i64 {
-    const KIND: Kind = Kind::INT64;
+macro_rules! elem {
+    ($ty:ty, $kind:ident) => {
+        impl Elem for $ty {
+            const KIND: Kind = Kind::$kind;
+        }
+    };
 }
+
+#[cfg(feature = "float16")]
+elem!(f16, FLOAT16);
+elem!(f32, FLOAT32);
+elem!(f64, FLOAT64);
+elem!(i8, SINT8);
+elem!(u8, UINT8);
+elem!(i16, SINT16);
+elem!(u16, UINT16);
+elem!(i32, INT32);
+elem!(i64, INT64);
+elem!(isize, CAML_INT);
+elem!(NativeInt, NATIVE_INT);
+#[cfg(feature = "complex")]
+elem!(Complex32, COMPLEX32);
+#[cfg(feature = "complex")]
+elem!(Complex64, COMPLEX64);
+elem!(OCamlChar, CHAR);
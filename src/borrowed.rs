@@ -0,0 +1,246 @@
+//! Arguments of type `&str`, `&[u8]`, `&[f64]` and `&[OCamlInt]`, as well as
+//! the mutable versions of the slices, are not copied, the stubs pass
+//! references that point directly to the content of the OCaml `string`,
+//! `bytes`, `float array` or `int array`. OCaml values can be moved by the GC,
+//! so these references are only valid as long as no OCaml allocation happens.
+//! The generated stubs only create them for the duration of the call, once all
+//! the other arguments have been converted, reject them in functions that
+//! release the runtime lock and do not allow functions to return references.
+//!
+//! `&FloatArray` and `&mut FloatArray` give the same direct access to a
+//! `float array` and can view its content as a slice of records. Records that
+//! are `#[repr(C)]` and only contain `f64` fields have the same layout as the
+//! OCaml flat float record, the bridge implements `FloatRecord` for them and
+//! `&Record` arguments then point to the OCaml block. Mutable references to
+//! records are still copied as OCaml records are immutable.
+//!
+//! Calling back into OCaml or allocating OCaml values from the function body
+//! could trigger a collection, so `call_named`, the `FnN::call*` methods,
+//! `RuntimeLock::release` and the allocations done by this library, e.g. in
+//! `ToValue` implementations, `RootedValue::create` or `BigArray1::new`, panic
+//! while borrowed arguments are live. Allocations done by calling the OCaml
+//! runtime directly are not checked.
+
+/// An element of an OCaml `int array`, the value is stored tagged so `&[OCamlInt]`
+/// can point to the array content without copying it.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OCamlInt(ocaml_sys::Value);
+
+impl OCamlInt {
+    pub fn new(i: isize) -> Self {
+        OCamlInt(unsafe { ocaml_sys::val_int(i) })
+    }
+
+    pub fn get(self) -> isize {
+        unsafe { ocaml_sys::int_val(self.0) }
+    }
+
+    pub fn set(&mut self, i: isize) {
+        self.0 = unsafe { ocaml_sys::val_int(i) }
+    }
+}
+
+/// Unsized types that can be borrowed from an OCaml value without copying.
+pub trait BorrowFromValue {
+    /// # Safety
+    /// The returned reference must not outlive the call and no OCaml allocation
+    /// can happen while it is live.
+    #[doc(hidden)]
+    unsafe fn borrow_from_value<'a>(v: ocaml_sys::Value) -> &'a Self;
+}
+
+/// Unsized types that can be mutably borrowed from an OCaml value without
+/// copying, e.g. `[u8]` for OCaml `bytes`.
+pub trait BorrowMutFromValue {
+    /// # Safety
+    /// Same as `BorrowFromValue::borrow_from_value`, in addition the OCaml value
+    /// must be mutable and not aliased by another argument.
+    #[doc(hidden)]
+    unsafe fn borrow_mut_from_value<'a>(v: ocaml_sys::Value) -> &'a mut Self;
+}
+
+/// Structs with the same layout as a flat OCaml float record, i.e. `#[repr(C)]`
+/// structs where all the fields are `f64`.
+///
+/// # Safety
+/// The type must be made of `FIELDS` `f64` values and have no padding.
+pub unsafe trait FloatRecord: Sized {
+    const FIELDS: usize;
+}
+
+/// The content of an OCaml `float array`, this derefs to a slice of `f64`.
+#[repr(transparent)]
+#[derive(Debug, PartialEq)]
+pub struct FloatArray([f64]);
+
+impl FloatArray {
+    fn from_slice(slice: &[f64]) -> &Self {
+        unsafe { &*(slice as *const [f64] as *const FloatArray) }
+    }
+
+    fn from_slice_mut(slice: &mut [f64]) -> &mut Self {
+        unsafe { &mut *(slice as *mut [f64] as *mut FloatArray) }
+    }
+
+    fn records_len<R: FloatRecord>(&self) -> usize {
+        let len = self.0.len() / R::FIELDS;
+        if len * R::FIELDS != self.0.len() {
+            crate::panic::invalid_argument(format!(
+                "float array length {} is not a multiple of {}",
+                self.0.len(),
+                R::FIELDS
+            ))
+        }
+        len
+    }
+
+    /// Views the array as consecutive records, raises `Invalid_argument` if the
+    /// length is not a multiple of the number of fields.
+    pub fn as_records<R: FloatRecord>(&self) -> &[R] {
+        let len = self.records_len::<R>();
+        unsafe { std::slice::from_raw_parts(self.0.as_ptr() as *const R, len) }
+    }
+
+    pub fn as_records_mut<R: FloatRecord>(&mut self) -> &mut [R] {
+        let len = self.records_len::<R>();
+        unsafe { std::slice::from_raw_parts_mut(self.0.as_mut_ptr() as *mut R, len) }
+    }
+}
+
+impl std::ops::Deref for FloatArray {
+    type Target = [f64];
+
+    fn deref(&self) -> &[f64] {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for FloatArray {
+    fn deref_mut(&mut self) -> &mut [f64] {
+        &mut self.0
+    }
+}
+
+impl BorrowFromValue for [u8] {
+    unsafe fn borrow_from_value<'a>(v: ocaml_sys::Value) -> &'a Self {
+        let len = ocaml_sys::caml_string_length(v);
+        std::slice::from_raw_parts(ocaml_sys::string_val(v), len)
+    }
+}
+
+impl BorrowMutFromValue for [u8] {
+    unsafe fn borrow_mut_from_value<'a>(v: ocaml_sys::Value) -> &'a mut Self {
+        let len = ocaml_sys::caml_string_length(v);
+        std::slice::from_raw_parts_mut(ocaml_sys::string_val(v), len)
+    }
+}
+
+impl BorrowFromValue for str {
+    unsafe fn borrow_from_value<'a>(v: ocaml_sys::Value) -> &'a Self {
+        match std::str::from_utf8(<[u8] as BorrowFromValue>::borrow_from_value(v)) {
+            Ok(s) => s,
+            Err(err) => crate::panic::invalid_argument(format!("invalid UTF-8 string: {err}")),
+        }
+    }
+}
+
+// Empty arrays are the atom with tag 0 rather than a double array.
+unsafe fn float_array_len(v: ocaml_sys::Value) -> usize {
+    let len = ocaml_sys::wosize_val(v);
+    let tag = ocaml_sys::tag_val(v);
+    if len == 0 {
+        0
+    } else if tag == ocaml_sys::DOUBLE_ARRAY {
+        len * std::mem::size_of::<ocaml_sys::Value>() / std::mem::size_of::<f64>()
+    } else {
+        crate::panic::invalid_argument(format!("unexpected tag for double array, {tag}"))
+    }
+}
+
+impl BorrowFromValue for [f64] {
+    unsafe fn borrow_from_value<'a>(v: ocaml_sys::Value) -> &'a Self {
+        std::slice::from_raw_parts(v as *const f64, float_array_len(v))
+    }
+}
+
+impl BorrowMutFromValue for [f64] {
+    unsafe fn borrow_mut_from_value<'a>(v: ocaml_sys::Value) -> &'a mut Self {
+        std::slice::from_raw_parts_mut(v as *mut f64, float_array_len(v))
+    }
+}
+
+impl BorrowFromValue for FloatArray {
+    unsafe fn borrow_from_value<'a>(v: ocaml_sys::Value) -> &'a Self {
+        FloatArray::from_slice(<[f64] as BorrowFromValue>::borrow_from_value(v))
+    }
+}
+
+impl BorrowMutFromValue for FloatArray {
+    unsafe fn borrow_mut_from_value<'a>(v: ocaml_sys::Value) -> &'a mut Self {
+        FloatArray::from_slice_mut(<[f64] as BorrowMutFromValue>::borrow_mut_from_value(v))
+    }
+}
+
+// A record value is a flat float block with one word per field on 64 bits.
+impl<R: FloatRecord> BorrowFromValue for R {
+    unsafe fn borrow_from_value<'a>(v: ocaml_sys::Value) -> &'a Self {
+        let tag = ocaml_sys::tag_val(v);
+        if tag != ocaml_sys::DOUBLE_ARRAY {
+            crate::panic::invalid_argument(format!("unexpected tag for float record, {tag}"))
+        }
+        let size = ocaml_sys::wosize_val(v) * std::mem::size_of::<ocaml_sys::Value>();
+        if size != std::mem::size_of::<R>() {
+            crate::panic::invalid_argument(format!("unexpected size for float record, {size}"))
+        }
+        &*(v as *const R)
+    }
+}
+
+// Writing an immediate over an immediate does not require caml_modify.
+impl BorrowFromValue for [OCamlInt] {
+    unsafe fn borrow_from_value<'a>(v: ocaml_sys::Value) -> &'a Self {
+        std::slice::from_raw_parts(v as *const OCamlInt, ocaml_sys::wosize_val(v))
+    }
+}
+
+impl BorrowMutFromValue for [OCamlInt] {
+    unsafe fn borrow_mut_from_value<'a>(v: ocaml_sys::Value) -> &'a mut Self {
+        std::slice::from_raw_parts_mut(v as *mut OCamlInt, ocaml_sys::wosize_val(v))
+    }
+}
+
+thread_local! {
+    static ACTIVE_BORROWS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// Marks the borrowed arguments of a stub as live until dropped.
+#[doc(hidden)]
+pub struct BorrowGuard(());
+
+impl BorrowGuard {
+    pub fn enter() -> Self {
+        ACTIVE_BORROWS.with(|borrows| borrows.set(borrows.get() + 1));
+        BorrowGuard(())
+    }
+}
+
+impl Drop for BorrowGuard {
+    fn drop(&mut self) {
+        ACTIVE_BORROWS.with(|borrows| borrows.set(borrows.get() - 1))
+    }
+}
+
+/// Panics if some borrowed arguments are live on the current thread, `what`
+/// is the operation that could move them.
+pub fn check_no_borrow(what: &str) {
+    if ACTIVE_BORROWS.with(|borrows| borrows.get()) > 0 {
+        panic!("cannot {what} while borrowed arguments are live")
+    }
+}
+
+// Called before allocating on the OCaml heap.
+#[inline]
+pub(crate) fn check_alloc() {
+    check_no_borrow("allocate OCaml values")
+}
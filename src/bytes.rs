@@ -0,0 +1,59 @@
+use crate::from_value::{FromSysValue, NotF64};
+use crate::to_value::ToValue;
+
+/// An owned byte buffer that is converted to and from OCaml `bytes`, whereas
+/// `Vec<u8>` is converted to and from OCaml `string`. The content is copied in
+/// both directions, use a `&mut [u8]` argument to modify OCaml `bytes` in place.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Bytes(pub Vec<u8>);
+
+impl Bytes {
+    pub fn new() -> Self {
+        Bytes(Vec::new())
+    }
+
+    pub fn into_inner(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl std::ops::Deref for Bytes {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for Bytes {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<Vec<u8>> for Bytes {
+    fn from(vs: Vec<u8>) -> Self {
+        Bytes(vs)
+    }
+}
+
+impl From<Bytes> for Vec<u8> {
+    fn from(vs: Bytes) -> Self {
+        vs.0
+    }
+}
+
+// OCaml strings and bytes share the same representation.
+impl ToValue for Bytes {
+    fn to_value(&self) -> ocaml_sys::Value {
+        self.0.to_value()
+    }
+}
+
+impl FromSysValue for Bytes {
+    unsafe fn from_value(v: ocaml_sys::Value) -> Self {
+        Bytes(<Vec<u8> as FromSysValue>::from_value(v))
+    }
+}
+
+impl NotF64 for Bytes {}
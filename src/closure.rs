@@ -32,12 +32,29 @@ fn handle_exn<'a, R: 'static + FromSysValue>(r: ocaml_sys::Value) -> crate::exn:
     }
 }
 
+/// Calls the OCaml function registered under `name` via `Callback.register`.
+/// This is used by the functions generated for `extern "OCaml"` blocks, the
+/// arguments have to be rooted by the caller.
+pub fn call_named<'a, Res>(name: &str, args: &mut [ocaml_sys::Value]) -> crate::exn::Result<'a, Res>
+where
+    Res: 'static + FromSysValue,
+{
+    crate::borrowed::check_no_borrow("call an OCaml function");
+    let c_name = std::ffi::CString::new(name).expect("unexpected nul byte in function name");
+    let f = unsafe { ocaml_sys::caml_named_value(c_name.as_ptr() as *const ocaml_sys::Char) };
+    if f.is_null() {
+        panic!("no OCaml function registered under the name {name}")
+    }
+    handle_exn(unsafe { ocaml_sys::caml_callbackN_exn(*f, args.len(), args.as_mut_ptr()) })
+}
+
 impl<Res> Fn0<Res>
 where
     Res: 'static + FromSysValue,
 {
     // This uses [mut self] as this can result in side effects on the ocaml side.
     pub fn call0<'a>(&mut self) -> crate::exn::Result<'a, Res> {
+        crate::borrowed::check_no_borrow("call an OCaml closure");
         let f = self.f.value().value;
         handle_exn(unsafe { ocaml_sys::caml_callback_exn(f, ocaml_sys::UNIT) })
     }
@@ -72,6 +89,7 @@ where
 {
     // This uses [mut self] as this can result in side effects on the ocaml side.
     pub fn call1<'a>(&mut self, arg: Arg) -> crate::exn::Result<'a, Res> {
+        crate::borrowed::check_no_borrow("call an OCaml closure");
         let arg = arg.to_value();
         handle_exn(unsafe { ocaml_sys::caml_callback_exn(self.f.value().value, arg) })
     }
@@ -107,6 +125,7 @@ where
 {
     // This uses [mut self] as this can result in side effects on the ocaml side.
     pub fn call2<'a>(&mut self, arg1: Arg1, arg2: Arg2) -> crate::exn::Result<'a, Res> {
+        crate::borrowed::check_no_borrow("call an OCaml closure");
         let arg1: RootedValue<()> = RootedValue::create(arg1.to_value());
         let arg2: RootedValue<()> = RootedValue::create(arg2.to_value());
         handle_exn(unsafe {
@@ -152,6 +171,7 @@ where
 {
     // This uses [mut self] as this can result in side effects on the ocaml side.
     pub fn call3<'a>(&mut self, arg1: Arg1, arg2: Arg2, arg3: Arg3) -> crate::exn::Result<'a, Res> {
+        crate::borrowed::check_no_borrow("call an OCaml closure");
         let arg1: RootedValue<()> = crate::RootedValue::create(arg1.to_value());
         let arg2: RootedValue<()> = crate::RootedValue::create(arg2.to_value());
         let arg3: RootedValue<()> = crate::RootedValue::create(arg3.to_value());
@@ -165,3 +185,157 @@ where
         })
     }
 }
+
+// Closures with more than three arguments go through caml_callbackN_exn.
+macro_rules! fn_n {
+    ($name:ident, $call:ident, $(($arg:ident, $ty:ident)),*) => {
+        pub struct $name<$($ty,)* Res>
+        where
+            $($ty: ToValue,)*
+            Res: 'static + FromSysValue,
+        {
+            f: crate::RootedValue<Res>,
+            phantom_data: std::marker::PhantomData<($($ty,)* Res)>,
+        }
+
+        impl<$($ty,)* Res> FromSysValue for $name<$($ty,)* Res>
+        where
+            $($ty: ToValue,)*
+            Res: 'static + FromSysValue,
+        {
+            unsafe fn from_value(f: ocaml_sys::Value) -> Self {
+                let f = crate::RootedValue::create(f);
+                $name { f, phantom_data: std::marker::PhantomData }
+            }
+        }
+
+        impl<$($ty,)* Res> $name<$($ty,)* Res>
+        where
+            $($ty: ToValue,)*
+            Res: 'static + FromSysValue,
+        {
+            // This uses [mut self] as this can result in side effects on the ocaml side.
+            #[allow(clippy::too_many_arguments)]
+            pub fn $call<'a>(&mut self, $($arg: $ty),*) -> crate::exn::Result<'a, Res> {
+                crate::borrowed::check_no_borrow("call an OCaml closure");
+                $(let $arg: RootedValue<()> = RootedValue::create($arg.to_value());)*
+                let mut args = [$($arg.value().value),*];
+                handle_exn(unsafe {
+                    ocaml_sys::caml_callbackN_exn(
+                        self.f.value().value,
+                        args.len(),
+                        args.as_mut_ptr(),
+                    )
+                })
+            }
+        }
+    };
+}
+
+fn_n!(Fn4, call4, (arg1, Arg1), (arg2, Arg2), (arg3, Arg3), (arg4, Arg4));
+fn_n!(Fn5, call5, (arg1, Arg1), (arg2, Arg2), (arg3, Arg3), (arg4, Arg4), (arg5, Arg5));
+fn_n!(
+    Fn6,
+    call6,
+    (arg1, Arg1),
+    (arg2, Arg2),
+    (arg3, Arg3),
+    (arg4, Arg4),
+    (arg5, Arg5),
+    (arg6, Arg6)
+);
+fn_n!(
+    Fn7,
+    call7,
+    (arg1, Arg1),
+    (arg2, Arg2),
+    (arg3, Arg3),
+    (arg4, Arg4),
+    (arg5, Arg5),
+    (arg6, Arg6),
+    (arg7, Arg7)
+);
+fn_n!(
+    Fn8,
+    call8,
+    (arg1, Arg1),
+    (arg2, Arg2),
+    (arg3, Arg3),
+    (arg4, Arg4),
+    (arg5, Arg5),
+    (arg6, Arg6),
+    (arg7, Arg7),
+    (arg8, Arg8)
+);
+
+/// The name under which the generated OCaml code registers the function
+/// turning the custom block holding a [RustFn1] into an OCaml closure.
+pub const RUST_FN1_NAME: &str = "ocaml_rust.rust_fn1\0";
+
+type ErasedFn1 = Box<dyn FnMut(ocaml_sys::Value) -> ocaml_sys::Value>;
+
+/// A Rust closure that is converted to an OCaml function of type `arg -> res`.
+/// The closure is stored in a custom block so that it gets dropped when the
+/// OCaml function is collected. The conversion only goes from Rust to OCaml.
+pub struct RustFn1<Arg, Res> {
+    f: crate::Custom<ErasedFn1>,
+    phantom_data: std::marker::PhantomData<(Arg, Res)>,
+}
+
+impl<Arg, Res> RustFn1<Arg, Res>
+where
+    Arg: FromSysValue,
+    Res: ToValue,
+{
+    pub fn new<F>(mut f: F) -> Self
+    where
+        F: FnMut(Arg) -> Res + 'static,
+    {
+        let f: ErasedFn1 = Box::new(move |arg| {
+            let arg = unsafe { Arg::from_value(arg) };
+            f(arg).to_value()
+        });
+        RustFn1 { f: crate::Custom::new(f), phantom_data: std::marker::PhantomData }
+    }
+}
+
+impl<Arg, Res> ToValue for RustFn1<Arg, Res> {
+    fn to_value(&self) -> ocaml_sys::Value {
+        let f: RootedValue<()> = RootedValue::create(self.f.to_value());
+        let make_closure = unsafe {
+            ocaml_sys::caml_named_value(RUST_FN1_NAME.as_ptr() as *const ocaml_sys::Char)
+        };
+        if make_closure.is_null() {
+            panic!("{} has not been registered", RUST_FN1_NAME.trim_end_matches('\0'))
+        }
+        let closure = unsafe { ocaml_sys::caml_callback_exn(*make_closure, f.value().value) };
+        if ocaml_sys::is_exception_result(closure) {
+            panic!("unexpected exception when building a closure")
+        }
+        closure
+    }
+}
+
+impl<Arg, Res> crate::from_value::NotF64 for RustFn1<Arg, Res> {}
+
+/// The trampoline called from the OCaml closures built for [RustFn1].
+/// The closure is protected by a mutex, a recursive call raises `Rust_panic`
+/// rather than deadlocking. A panic in a previous call does not prevent
+/// calling the closure again.
+#[no_mangle]
+pub extern "C" fn __ocaml_rust_fn1_call(
+    f: ocaml_sys::Value,
+    arg: ocaml_sys::Value,
+) -> ocaml_sys::Value {
+    crate::panic::catch_panic(move || {
+        let f: crate::Custom<ErasedFn1> = unsafe { FromSysValue::from_value(f) };
+        let mut f = match f.inner().try_lock() {
+            Ok(f) => f,
+            Err(std::sync::TryLockError::Poisoned(err)) => err.into_inner(),
+            Err(std::sync::TryLockError::WouldBlock) => {
+                panic!("a RustFn1 closure cannot be called while it is already running")
+            }
+        };
+        f(arg)
+    })
+}
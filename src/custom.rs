@@ -49,6 +49,7 @@ impl<T> Custom<T> {
 
 impl<T: 'static> crate::to_value::ToValue for Custom<T> {
     fn to_value(&self) -> ocaml_sys::Value {
+        crate::borrowed::check_alloc();
         let box_: Box<Box<dyn std::any::Any>> = Box::new(Box::new(self._inner.clone()));
         let boxed_t = Box::into_raw(box_);
         let sys_value = unsafe {
@@ -85,6 +86,7 @@ impl<T> CustomConst<T> {
 
 impl<T: 'static> crate::to_value::ToValue for CustomConst<T> {
     fn to_value(&self) -> ocaml_sys::Value {
+        crate::borrowed::check_alloc();
         let box_: Box<Box<dyn std::any::Any>> = Box::new(Box::new(self._inner.clone()));
         let boxed_t = Box::into_raw(box_);
         let sys_value = unsafe {
@@ -1,9 +1,159 @@
+use crate::to_value::ToValue;
+
 pub type RustError = Box<dyn std::error::Error>;
 pub type RustResult<T> = std::result::Result<T, RustError>;
 
-impl crate::to_value::ToValue for RustError {
+impl ToValue for RustError {
     fn to_value(&self) -> ocaml_sys::Value {
         // This is handled as a string both here and in the ocaml code generation.
         self.to_string().to_value()
     }
 }
+
+/// The name under which the generated OCaml code registers its `Rust_error`
+/// exception via `Callback.register_exception`.
+pub const RUST_ERROR_EXN_NAME: &str = "ocaml_rust.rust_error\0";
+
+/// The kind of a [RustError], this is converted to the OCaml
+/// `Rust_error.kind` variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    Io,
+    Parse,
+    Other,
+}
+
+/// A structured version of a [RustError], converted to the OCaml
+/// `Rust_error.t` record generated by `gen/cmd`.
+#[derive(Debug, Clone)]
+pub struct ErrorDetails {
+    pub kind: ErrorKind,
+    pub message: String,
+    pub sources: Vec<String>,
+}
+
+type KindOf = Box<dyn Fn(&(dyn std::error::Error + 'static)) -> Option<ErrorKind> + Send + Sync>;
+
+static KIND_OF: std::sync::RwLock<Vec<(std::any::TypeId, KindOf)>> =
+    std::sync::RwLock::new(Vec::new());
+
+/// Registers how to get the kind of errors of type `E`, e.g. for error types
+/// from other crates. Returning `None` lets the kind be found from the source
+/// chain. Registered types take precedence over the std ones, registering the
+/// same type again replaces the previous function.
+pub fn register_error_kind<E>(kind_of: fn(&E) -> Option<ErrorKind>)
+where
+    E: std::error::Error + 'static,
+{
+    let type_id = std::any::TypeId::of::<E>();
+    let kind_of: KindOf = Box::new(move |err| err.downcast_ref::<E>().and_then(kind_of));
+    let mut kinds = KIND_OF.write().unwrap_or_else(|err| err.into_inner());
+    kinds.retain(|(id, _)| *id != type_id);
+    kinds.push((type_id, kind_of))
+}
+
+fn registered_error_kind(err: &(dyn std::error::Error + 'static)) -> Option<ErrorKind> {
+    let kinds = KIND_OF.read().unwrap_or_else(|err| err.into_inner());
+    kinds.iter().find_map(|(_, kind_of)| kind_of(err))
+}
+
+fn error_kind(err: &(dyn std::error::Error + 'static)) -> Option<ErrorKind> {
+    if let Some(kind) = registered_error_kind(err) {
+        Some(kind)
+    } else if err.is::<std::io::Error>() {
+        Some(ErrorKind::Io)
+    } else if err.is::<std::num::ParseIntError>()
+        || err.is::<std::num::ParseFloatError>()
+        || err.is::<std::str::ParseBoolError>()
+        || err.is::<std::char::ParseCharError>()
+        || err.is::<std::net::AddrParseError>()
+        || err.is::<std::str::Utf8Error>()
+        || err.is::<std::string::FromUtf8Error>()
+    {
+        Some(ErrorKind::Parse)
+    } else {
+        None
+    }
+}
+
+impl ErrorDetails {
+    /// The kind is the one of the first error in the source chain that has
+    /// a known type, either from std or registered via [register_error_kind].
+    pub fn new(err: &(dyn std::error::Error + 'static)) -> Self {
+        let mut kind = error_kind(err);
+        let mut sources = vec![];
+        let mut source = err.source();
+        while let Some(err) = source {
+            kind = kind.or_else(|| error_kind(err));
+            sources.push(err.to_string());
+            source = err.source();
+        }
+        ErrorDetails { kind: kind.unwrap_or(ErrorKind::Other), message: err.to_string(), sources }
+    }
+}
+
+impl ToValue for ErrorKind {
+    fn to_value(&self) -> ocaml_sys::Value {
+        let v = match self {
+            Self::Io => 0,
+            Self::Parse => 1,
+            Self::Other => 2,
+        };
+        unsafe { ocaml_sys::val_int(v) }
+    }
+}
+
+impl ToValue for ErrorDetails {
+    fn to_value(&self) -> ocaml_sys::Value {
+        let v = unsafe { ocaml_sys::caml_alloc_tuple(3) };
+        let rv: crate::RootedValue<()> = crate::RootedValue::create(v);
+        let kind = self.kind.to_value();
+        unsafe { ocaml_sys::store_field(rv.value().value, 0, kind) };
+        let message = self.message.to_value();
+        unsafe { ocaml_sys::store_field(rv.value().value, 1, message) };
+        let sources = self.sources.to_value();
+        unsafe { ocaml_sys::store_field(rv.value().value, 2, sources) };
+        rv.value().value
+    }
+}
+
+impl crate::from_value::NotF64 for ErrorDetails {}
+
+/// Converts a [RustResult] to an OCaml `('a, Rust_error.t) Result.t`.
+pub fn to_value_with_details<T>(res: &RustResult<T>) -> ocaml_sys::Value
+where
+    T: ToValue,
+{
+    let res = res.as_ref().map_err(|err| ErrorDetails::new(err.as_ref()));
+    res.to_value()
+}
+
+/// Converts the ok and error cases of a [RustResult] separately, the error
+/// case being converted to a `Rust_error.t`. The returned values are not
+/// rooted so the error should be raised via [raise_rust_error] before any
+/// further allocation.
+pub fn to_value_or_details<T>(
+    res: &RustResult<T>,
+) -> std::result::Result<ocaml_sys::Value, ocaml_sys::Value>
+where
+    T: ToValue,
+{
+    match res {
+        Ok(v) => Ok(v.to_value()),
+        Err(err) => Err(ErrorDetails::new(err.as_ref()).to_value()),
+    }
+}
+
+/// Raises the OCaml `Rust_error` exception, or a `Failure` with the error
+/// message if the exception has not been registered.
+pub fn raise_rust_error(details: ocaml_sys::Value) -> ! {
+    let exn = unsafe {
+        ocaml_sys::caml_named_value(RUST_ERROR_EXN_NAME.as_ptr() as *const ocaml_sys::Char)
+    };
+    if exn.is_null() {
+        unsafe { ocaml_sys::caml_failwith_value(*ocaml_sys::field(details, 1)) }
+    } else {
+        unsafe { ocaml_sys::caml_raise_with_arg(*exn, details) }
+    }
+    unreachable!()
+}
@@ -6,6 +6,10 @@ pub struct OCamlExn {
 pub type OCamlError<'a> = crate::value::Value<'a, OCamlExn>;
 pub type Result<'a, T> = std::result::Result<T, OCamlError<'a>>;
 
+/// The result type to use for Rust functions that propagate OCaml exceptions,
+/// when used as a return type in a bridge the exception gets re-raised unchanged.
+pub type OCamlResult<T> = std::result::Result<T, RootedExn>;
+
 impl<'a> std::fmt::Debug for OCamlError<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let v = crate::from_value::FromValue::from_value(self);
@@ -27,3 +31,122 @@ impl crate::from_value::FromSysValue for OCamlExn {
         OCamlExn { message: message.to_string() }
     }
 }
+
+// Exceptions without arguments are represented by their slot, a block with
+// tag Object_tag whose first field is the constructor name. Exceptions with
+// arguments are blocks holding the slot followed by the arguments.
+fn exn_slot(v: ocaml_sys::Value) -> ocaml_sys::Value {
+    unsafe {
+        if ocaml_sys::tag_val(v) == ocaml_sys::OBJECT {
+            v
+        } else {
+            *ocaml_sys::field(v, 0)
+        }
+    }
+}
+
+impl<'a> OCamlError<'a> {
+    /// The name of the exception constructor, e.g. `Not_found` for predefined
+    /// exceptions or `Dune__exe__Test.Foo` for exceptions defined in a module.
+    pub fn constructor_name(&self) -> String {
+        let name = unsafe { *ocaml_sys::field(exn_slot(self.value), 0) };
+        unsafe { <String as crate::from_value::FromSysValue>::from_value(name) }
+    }
+
+    /// The number of arguments carried by the exception.
+    pub fn num_args(&self) -> usize {
+        if unsafe { ocaml_sys::tag_val(self.value) } == ocaml_sys::OBJECT {
+            0
+        } else {
+            unsafe { ocaml_sys::wosize_val(self.value) - 1 }
+        }
+    }
+
+    /// The argument at position `index`, e.g. the integer for `exception Foo of int`.
+    pub fn arg<T>(&self, index: usize) -> Option<crate::Value<'a, T>> {
+        if index < self.num_args() {
+            Some(unsafe { crate::Value::new(*ocaml_sys::field(self.value, index + 1)) })
+        } else {
+            None
+        }
+    }
+
+    /// Converts the payload of an exception with a single argument.
+    pub fn payload<T>(&self) -> Option<T>
+    where
+        T: crate::from_value::FromSysValue,
+    {
+        if self.num_args() == 1 {
+            Some(unsafe { T::from_value(*ocaml_sys::field(self.value, 1)) })
+        } else {
+            None
+        }
+    }
+
+    /// Checks whether this is the exception registered on the OCaml side
+    /// with `Callback.register_exception name`, whatever its arguments.
+    pub fn is_registered(&self, name: &str) -> bool {
+        let c_name = match std::ffi::CString::new(name) {
+            Ok(c_name) => c_name,
+            Err(_) => return false,
+        };
+        let exn = unsafe { ocaml_sys::caml_named_value(c_name.as_ptr() as *const ocaml_sys::Char) };
+        !exn.is_null() && exn_slot(unsafe { *exn }) == exn_slot(self.value)
+    }
+
+    /// Roots the exception so that it can be kept around and re-raised.
+    pub fn to_rooted(&self) -> RootedExn {
+        RootedExn { exn: crate::RootedValue::create(self.value) }
+    }
+}
+
+/// An OCaml exception that is protected from the GC, this is used
+/// to propagate exceptions back to the OCaml caller.
+pub struct RootedExn {
+    exn: crate::RootedValue<OCamlExn>,
+}
+
+impl RootedExn {
+    pub fn value(&self) -> OCamlError<'_> {
+        self.exn.value()
+    }
+}
+
+impl<'a> From<OCamlError<'a>> for RootedExn {
+    fn from(exn: OCamlError<'a>) -> Self {
+        exn.to_rooted()
+    }
+}
+
+impl std::fmt::Debug for RootedExn {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&self.value(), f)
+    }
+}
+
+impl std::fmt::Display for RootedExn {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&self.value(), f)
+    }
+}
+
+/// Converts the ok case of an [OCamlResult], in the error case the exception
+/// is returned. The returned values are not rooted so the exception should be
+/// raised via [raise] before any further allocation.
+pub fn to_value_or_exn<T>(
+    res: &OCamlResult<T>,
+) -> std::result::Result<ocaml_sys::Value, ocaml_sys::Value>
+where
+    T: crate::to_value::ToValue,
+{
+    match res {
+        Ok(v) => Ok(v.to_value()),
+        Err(exn) => Err(exn.value().value),
+    }
+}
+
+/// Raises an exception unchanged.
+pub fn raise(exn: ocaml_sys::Value) -> ! {
+    unsafe { ocaml_sys::caml_raise(exn) };
+    unreachable!()
+}
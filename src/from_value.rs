@@ -8,6 +8,10 @@ pub trait FromValue: Sized {
     fn from_value(v: &Value<Self>) -> Self;
 }
 
+impl FromSysValue for () {
+    unsafe fn from_value(_v: ocaml_sys::Value) -> Self {}
+}
+
 impl FromSysValue for u8 {
     unsafe fn from_value(v: ocaml_sys::Value) -> Self {
         ocaml_sys::int_val(v) as u8
@@ -35,7 +39,7 @@ impl FromSysValue for bool {
 impl FromSysValue for f32 {
     unsafe fn from_value(v: ocaml_sys::Value) -> Self {
         check_tag("double", v, ocaml_sys::DOUBLE);
-        *(v as *const f32)
+        *(v as *const f64) as f32
     }
 }
 
@@ -70,12 +74,63 @@ impl FromSysValue for Vec<u8> {
     }
 }
 
+thread_local! {
+    static STRICT_UTF8: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// While this guard is alive, converting an OCaml string that is not valid
+/// UTF-8 to a `String` raises `Invalid_argument` rather than replacing the
+/// invalid sequences. This is used for functions with `#[strict_utf8]`.
+#[doc(hidden)]
+pub struct StrictUtf8(bool);
+
+impl StrictUtf8 {
+    pub fn enable() -> Self {
+        StrictUtf8(STRICT_UTF8.with(|strict| strict.replace(true)))
+    }
+}
+
+impl Drop for StrictUtf8 {
+    fn drop(&mut self) {
+        STRICT_UTF8.with(|strict| strict.set(self.0))
+    }
+}
+
 impl FromSysValue for String {
     unsafe fn from_value(v: ocaml_sys::Value) -> Self {
         let len = ocaml_sys::caml_string_length(v);
         let start_ptr = ocaml_sys::string_val(v);
         let slice = std::slice::from_raw_parts(start_ptr, len);
-        String::from_utf8_lossy(slice).into_owned()
+        if STRICT_UTF8.with(|strict| strict.get()) {
+            match std::str::from_utf8(slice) {
+                Ok(s) => s.to_string(),
+                Err(err) => crate::panic::invalid_argument(format!("invalid UTF-8 string: {err}")),
+            }
+        } else {
+            String::from_utf8_lossy(slice).into_owned()
+        }
+    }
+}
+
+// OS strings are arbitrary bytes on unix so the conversion is lossless there.
+#[cfg(unix)]
+impl FromSysValue for std::ffi::OsString {
+    unsafe fn from_value(v: ocaml_sys::Value) -> Self {
+        use std::os::unix::ffi::OsStringExt;
+        std::ffi::OsString::from_vec(<Vec<u8> as FromSysValue>::from_value(v))
+    }
+}
+
+#[cfg(not(unix))]
+impl FromSysValue for std::ffi::OsString {
+    unsafe fn from_value(v: ocaml_sys::Value) -> Self {
+        std::ffi::OsString::from(<String as FromSysValue>::from_value(v))
+    }
+}
+
+impl FromSysValue for std::path::PathBuf {
+    unsafe fn from_value(v: ocaml_sys::Value) -> Self {
+        std::path::PathBuf::from(<std::ffi::OsString as FromSysValue>::from_value(v))
     }
 }
 
@@ -91,7 +146,8 @@ pub unsafe fn check_tag(kind: &str, v: ocaml_sys::Value, expected: u8) {
     }
 }
 
-unsafe fn check_tuple(v: ocaml_sys::Value, expected_len: usize) {
+#[doc(hidden)]
+pub unsafe fn check_tuple(v: ocaml_sys::Value, expected_len: usize) {
     check_tag("tuple", v, 0);
     let len = ocaml_sys::wosize_val(v);
     if len != expected_len {
@@ -189,17 +245,27 @@ vec_float!(f64);
 // The need for this hack will be removed once trait specialization
 // is stable.
 // https://rust-lang.github.io/rfcs/1210-impl-specialization.html
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` cannot be used as an array element here",
+    note = "OCaml arrays of floats and of unboxed float newtypes use a flat layout, use Vec<f64> instead"
+)]
 pub trait NotF64 {}
 
 impl NotF64 for i64 {}
 impl NotF64 for i32 {}
 impl NotF64 for String {}
+impl NotF64 for std::ffi::OsString {}
+impl NotF64 for std::path::PathBuf {}
 impl NotF64 for isize {}
 impl NotF64 for usize {}
 impl NotF64 for () {}
 impl<T> NotF64 for &T {}
 impl<T> NotF64 for Vec<T> {}
 impl<T> NotF64 for Option<T> {}
+impl<K, V, S> NotF64 for std::collections::HashMap<K, V, S> {}
+impl<K, V> NotF64 for std::collections::BTreeMap<K, V> {}
+impl<T, S> NotF64 for std::collections::HashSet<T, S> {}
+impl<T> NotF64 for std::collections::BTreeSet<T> {}
 impl<T1> NotF64 for (T1,) {}
 impl<T1, T2> NotF64 for (T1, T2) {}
 impl<T1, T2, T3> NotF64 for (T1, T2, T3) {}
@@ -265,6 +331,81 @@ where
     }
 }
 
+// Association lists can bind the same key multiple times, rather than picking
+// one of the bindings this raises Invalid_argument. Duplicates in lists converted
+// to sets are rejected in the same way.
+fn duplicate(container: &str) -> ! {
+    crate::panic::invalid_argument(format!("duplicate element in list converted to {container}"))
+}
+
+impl<K, V, S> FromSysValue for std::collections::HashMap<K, V, S>
+where
+    K: FromSysValue + Eq + std::hash::Hash,
+    V: FromSysValue,
+    S: std::hash::BuildHasher + Default,
+{
+    unsafe fn from_value(v: ocaml_sys::Value) -> Self {
+        let kvs: Vec<(K, V)> = crate::list::list_from_value(v);
+        let mut map = Self::with_capacity_and_hasher(kvs.len(), S::default());
+        for (k, v) in kvs {
+            if map.insert(k, v).is_some() {
+                duplicate("HashMap")
+            }
+        }
+        map
+    }
+}
+
+impl<K, V> FromSysValue for std::collections::BTreeMap<K, V>
+where
+    K: FromSysValue + Ord,
+    V: FromSysValue,
+{
+    unsafe fn from_value(v: ocaml_sys::Value) -> Self {
+        let kvs: Vec<(K, V)> = crate::list::list_from_value(v);
+        let mut map = Self::new();
+        for (k, v) in kvs {
+            if map.insert(k, v).is_some() {
+                duplicate("BTreeMap")
+            }
+        }
+        map
+    }
+}
+
+impl<T, S> FromSysValue for std::collections::HashSet<T, S>
+where
+    T: FromSysValue + Eq + std::hash::Hash,
+    S: std::hash::BuildHasher + Default,
+{
+    unsafe fn from_value(v: ocaml_sys::Value) -> Self {
+        let vs: Vec<T> = crate::list::list_from_value(v);
+        let mut set = Self::with_capacity_and_hasher(vs.len(), S::default());
+        for v in vs {
+            if !set.insert(v) {
+                duplicate("HashSet")
+            }
+        }
+        set
+    }
+}
+
+impl<T> FromSysValue for std::collections::BTreeSet<T>
+where
+    T: FromSysValue + Ord,
+{
+    unsafe fn from_value(v: ocaml_sys::Value) -> Self {
+        let vs: Vec<T> = crate::list::list_from_value(v);
+        let mut set = Self::new();
+        for v in vs {
+            if !set.insert(v) {
+                duplicate("BTreeSet")
+            }
+        }
+        set
+    }
+}
+
 impl<T> FromValue for T
 where
     T: FromSysValue,
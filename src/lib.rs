@@ -1,37 +1,38 @@
 // TODO:
 //   - Call from Rust, start the OCaml runtime in that case?
-//   - Dedicated extern "OCaml" section.
 //   - Provide a way to specify/override the generated ocaml types.
 pub mod bigarray;
+pub mod borrowed;
+pub mod bytes;
 pub mod closure;
 pub mod custom;
 pub mod error;
 pub mod exn;
 pub mod from_value;
 pub mod gc;
+pub mod list;
+pub mod panic;
 pub mod rooted;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod to_value;
 pub mod value;
-pub use bigarray::BigArray1;
+pub use bigarray::{BigArray1, BigArray2, BigArray3, CLayout, FortranLayout, Genarray};
+pub use borrowed::{FloatArray, FloatRecord, OCamlInt};
+pub use bytes::Bytes;
 pub use custom::{Custom, CustomConst};
 pub use error::{RustError, RustResult};
-pub use exn::OCamlExn;
-pub use ocaml_rust_macro::bridge;
+pub use exn::{OCamlExn, OCamlResult, RootedExn};
+pub use list::VecList;
+pub use ocaml_rust_macro::{bridge, FromValue, ToValue};
 pub use rooted::RootedValue;
 pub use value::Value;
 
-static PANIC_HOOK_SETUP: std::sync::Once = std::sync::Once::new();
-
+/// The panic hook is now installed by the generated stubs on their first call,
+/// this is kept for existing callers.
+#[deprecated(note = "panics are caught by the generated stubs, this call is not needed anymore")]
 pub fn initial_setup() {
-    PANIC_HOOK_SETUP.call_once(|| unsafe {
-        std::panic::set_hook(Box::new(|panic_info| {
-            let panic_info = panic_info.to_string();
-            let v = ocaml_sys::caml_alloc_string(panic_info.len());
-            let ptr = ocaml_sys::string_val(v);
-            core::ptr::copy_nonoverlapping(panic_info.as_ptr(), ptr, panic_info.len());
-            ocaml_sys::caml_failwith_value(v);
-        }))
-    });
+    panic::setup_panic_hook()
 }
 
 /// A struct to represent having released the OCaml runtime lock. The
@@ -41,6 +42,7 @@ pub struct RuntimeLock {}
 
 impl RuntimeLock {
     pub fn release() -> Self {
+        borrowed::check_no_borrow("release the runtime lock");
         unsafe { ocaml_sys::caml_enter_blocking_section() };
         RuntimeLock {}
     }
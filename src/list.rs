@@ -0,0 +1,114 @@
+use crate::from_value::{check_tag, FromSysValue, NotF64};
+use crate::rooted::RootedValue;
+use crate::to_value::ToValue;
+
+/// A vector that is converted to and from an OCaml list, `Vec` being
+/// converted to and from an OCaml array.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct VecList<T>(pub Vec<T>);
+
+impl<T> VecList<T> {
+    pub fn new() -> Self {
+        VecList(Vec::new())
+    }
+
+    pub fn into_inner(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T> std::ops::Deref for VecList<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> std::ops::DerefMut for VecList<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T> From<Vec<T>> for VecList<T> {
+    fn from(vs: Vec<T>) -> Self {
+        VecList(vs)
+    }
+}
+
+impl<T> From<VecList<T>> for Vec<T> {
+    fn from(vs: VecList<T>) -> Self {
+        vs.0
+    }
+}
+
+impl<T> FromIterator<T> for VecList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        VecList(iter.into_iter().collect())
+    }
+}
+
+impl<T> IntoIterator for VecList<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+// Builds a list by prepending the elements in iteration order, the resulting
+// list is in reverse order compared to the iterator.
+pub(crate) fn rev_list_to_value<T, I>(iter: I) -> ocaml_sys::Value
+where
+    T: ToValue,
+    I: Iterator<Item = T>,
+{
+    let mut list: RootedValue<()> = RootedValue::create(ocaml_sys::EMPTY_LIST);
+    for v in iter {
+        let cons: RootedValue<()> =
+            RootedValue::create(unsafe { ocaml_sys::caml_alloc(2, ocaml_sys::TAG_CONS) });
+        let v = T::to_value(&v);
+        unsafe { ocaml_sys::store_field(cons.value().value, 0, v) };
+        unsafe { ocaml_sys::store_field(cons.value().value, 1, list.value().value) };
+        list = cons;
+    }
+    list.value().value
+}
+
+pub(crate) unsafe fn list_from_value<T, C>(mut v: ocaml_sys::Value) -> C
+where
+    T: FromSysValue,
+    C: FromIterator<T>,
+{
+    let mut vs = Vec::new();
+    while v != ocaml_sys::EMPTY_LIST {
+        check_tag("list", v, ocaml_sys::TAG_CONS);
+        vs.push(FromSysValue::from_value(*ocaml_sys::field(v, 0)));
+        v = *ocaml_sys::field(v, 1);
+    }
+    vs.into_iter().collect()
+}
+
+// List elements are always boxed, including floats, so there is no need
+// for the NotF64 hack here.
+impl<T> ToValue for VecList<T>
+where
+    T: ToValue,
+{
+    fn to_value(&self) -> ocaml_sys::Value {
+        rev_list_to_value(self.0.iter().rev())
+    }
+}
+
+impl<T> FromSysValue for VecList<T>
+where
+    T: FromSysValue,
+{
+    unsafe fn from_value(v: ocaml_sys::Value) -> Self {
+        VecList(list_from_value(v))
+    }
+}
+
+impl<T> NotF64 for VecList<T> {}
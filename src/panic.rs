@@ -0,0 +1,102 @@
+// Rust panics must not unwind through the OCaml runtime frames. The generated
+// stubs catch them with [catch_unwind], drop all the Rust state and only then
+// raise the [Rust_panic] exception declared in the generated OCaml code.
+use std::cell::RefCell;
+
+/// The name under which the generated OCaml code registers its `Rust_panic`
+/// exception via `Callback.register_exception`.
+pub const RUST_PANIC_EXN_NAME: &str = "ocaml_rust.rust_panic\0";
+
+static PANIC_HOOK_SETUP: std::sync::Once = std::sync::Once::new();
+
+/// A panic payload that the stubs convert to an OCaml `Invalid_argument`
+/// exception rather than `Rust_panic`, used when an argument cannot be decoded.
+#[derive(Debug)]
+pub struct InvalidArgument(pub String);
+
+/// Aborts the current call, the stub then raises `Invalid_argument` with the
+/// given message once the Rust state has been dropped.
+pub fn invalid_argument(message: String) -> ! {
+    std::panic::panic_any(InvalidArgument(message))
+}
+
+thread_local! {
+    static PANIC_LOCATION: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+// The panic payload does not include the panic location so it is recorded by
+// a hook that then defers to the previously installed one.
+pub(crate) fn setup_panic_hook() {
+    PANIC_HOOK_SETUP.call_once(|| {
+        let previous_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |panic_info| {
+            if panic_info.payload().is::<InvalidArgument>() {
+                return;
+            }
+            let location = panic_info.location().map(|location| location.to_string());
+            PANIC_LOCATION.with(|l| *l.borrow_mut() = location);
+            previous_hook(panic_info)
+        }))
+    });
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    let message = if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.to_string()
+    } else {
+        "Box<dyn Any>".to_string()
+    };
+    match PANIC_LOCATION.with(|l| l.borrow_mut().take()) {
+        None => format!("panicked at '{message}'"),
+        Some(location) => format!("panicked at '{message}', {location}"),
+    }
+}
+
+fn raise_invalid_argument(message: String) -> ! {
+    let v = unsafe { ocaml_sys::caml_alloc_string(message.len()) };
+    let ptr = unsafe { ocaml_sys::string_val(v) };
+    unsafe { std::ptr::copy_nonoverlapping(message.as_ptr(), ptr, message.len()) };
+    drop(message);
+    unsafe { ocaml_sys::caml_invalid_argument_value(v) };
+    unreachable!()
+}
+
+fn raise_rust_panic(message: String) -> ! {
+    let v = unsafe { ocaml_sys::caml_alloc_string(message.len()) };
+    let ptr = unsafe { ocaml_sys::string_val(v) };
+    unsafe { std::ptr::copy_nonoverlapping(message.as_ptr(), ptr, message.len()) };
+    drop(message);
+    let exn = unsafe {
+        ocaml_sys::caml_named_value(RUST_PANIC_EXN_NAME.as_ptr() as *const ocaml_sys::Char)
+    };
+    if exn.is_null() {
+        unsafe { ocaml_sys::caml_failwith_value(v) }
+    } else {
+        unsafe { ocaml_sys::caml_raise_with_arg(*exn, v) }
+    }
+    unreachable!()
+}
+
+/// Run [f], converting a Rust panic into an OCaml `Rust_panic` exception. If
+/// the exception has not been registered, a `Failure` is raised instead.
+/// Panics with an `InvalidArgument` payload raise `Invalid_argument`.
+pub fn catch_panic<F, T>(f: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    setup_panic_hook();
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)) {
+        Ok(v) => v,
+        Err(payload) => {
+            let payload = match payload.downcast::<InvalidArgument>() {
+                Ok(invalid_argument) => raise_invalid_argument(invalid_argument.0),
+                Err(payload) => payload,
+            };
+            let message = panic_message(payload.as_ref());
+            drop(payload);
+            raise_rust_panic(message)
+        }
+    }
+}
@@ -8,6 +8,7 @@ where
 
 impl<T> RootedValue<T> {
     pub fn create(v: ocaml_sys::Value) -> RootedValue<T> {
+        crate::borrowed::check_alloc();
         RootedValue {
             root: unsafe { ocaml_boxroot_sys::boxroot_create(v).unwrap() },
             phantom_data: std::marker::PhantomData,
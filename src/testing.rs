@@ -0,0 +1,209 @@
+//! A harness to check bridged code from `cargo test`. The OCaml bindings are
+//! generated for a bridge file, then compiled with `ocamlfind ocamlopt`
+//! together with OCaml snippets and the static library built by cargo. The
+//! output of running the resulting executable is compared with the expected
+//! one, in the same way as with `ppx_expect`.
+//!
+//! ```ignore
+//! let harness = ocaml_rust::testing::Harness::new("src/lib.rs", "target/debug/libfoo.a");
+//! harness.expect("Printf.printf \"%d\\n\" (Ffi.add_one 41)", "42").unwrap();
+//! ```
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("{cmd} failed with {status}:\n{stderr}")]
+    Command { cmd: String, status: std::process::ExitStatus, stderr: String },
+
+    #[error("unexpected output:\n{diff}")]
+    Mismatch { expected: String, actual: String, diff: String },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The module name under which the generated bindings are available, the
+/// snippets are compiled with this module opened.
+pub const BINDINGS_MODULE: &str = "Bindings";
+
+static COUNTER: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+pub struct Harness {
+    rust_file: PathBuf,
+    static_lib: PathBuf,
+    generator: Vec<String>,
+    packages: Vec<String>,
+    build_dir: PathBuf,
+}
+
+impl Harness {
+    /// The generator defaults to the `ocaml-rust` binary from `gen/cmd`, this
+    /// can be overridden via the `OCAML_RUST_GEN` environment variable.
+    pub fn new<P1: AsRef<Path>, P2: AsRef<Path>>(rust_file: P1, static_lib: P2) -> Self {
+        let generator = match std::env::var("OCAML_RUST_GEN") {
+            Ok(generator) => generator.split_whitespace().map(String::from).collect(),
+            Err(_) => vec!["ocaml-rust".to_string()],
+        };
+        Harness {
+            rust_file: rust_file.as_ref().to_path_buf(),
+            static_lib: static_lib.as_ref().to_path_buf(),
+            generator,
+            packages: vec![],
+            build_dir: std::env::temp_dir().join("ocaml-rust-testing"),
+        }
+    }
+
+    /// The command used to generate the bindings, `--rust-file` and
+    /// `--ocaml-file` get appended to it.
+    pub fn generator<S: AsRef<str>>(mut self, cmd: &[S]) -> Self {
+        self.generator = cmd.iter().map(|s| s.as_ref().to_string()).collect();
+        self
+    }
+
+    /// Adds an ocamlfind package to compile the bindings and snippets with.
+    pub fn package<S: Into<String>>(mut self, package: S) -> Self {
+        self.packages.push(package.into());
+        self
+    }
+
+    pub fn build_dir<P: AsRef<Path>>(mut self, build_dir: P) -> Self {
+        self.build_dir = build_dir.as_ref().to_path_buf();
+        self
+    }
+
+    /// Returns false when no OCaml toolchain can be found, tests can use this
+    /// to be skipped rather than fail.
+    pub fn is_available() -> bool {
+        Command::new("ocamlfind").arg("ocamlopt").arg("-version").output().is_ok()
+    }
+
+    fn fresh_dir(&self) -> Result<PathBuf> {
+        let index = COUNTER.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        let dir = self.build_dir.join(format!("{}-{}", std::process::id(), index));
+        std::fs::create_dir_all(&dir)?;
+        Ok(dir)
+    }
+
+    /// Generates the OCaml bindings in the given directory.
+    pub fn generate<P: AsRef<Path>>(&self, dir: P) -> Result<PathBuf> {
+        let ocaml_file = dir.as_ref().join(format!("{}.ml", BINDINGS_MODULE.to_lowercase()));
+        let mut cmd = Command::new(&self.generator[0]);
+        cmd.args(&self.generator[1..])
+            .arg("--rust-file")
+            .arg(&self.rust_file)
+            .arg("--ocaml-file")
+            .arg(&ocaml_file);
+        run(cmd)?;
+        Ok(ocaml_file)
+    }
+
+    /// Compiles the snippet against the bindings and returns the output of
+    /// running it.
+    pub fn run(&self, snippet: &str) -> Result<String> {
+        let dir = self.fresh_dir()?;
+        let bindings = self.generate(&dir)?;
+        let snippet_file = dir.join("snippet.ml");
+        std::fs::write(&snippet_file, snippet)?;
+        let exe = dir.join("snippet.exe");
+        let mut cmd = Command::new("ocamlfind");
+        cmd.arg("ocamlopt");
+        if !self.packages.is_empty() {
+            cmd.arg("-package").arg(self.packages.join(","));
+        }
+        cmd.arg("-linkpkg")
+            .arg("-I")
+            .arg(&dir)
+            .arg(&bindings)
+            .arg("-open")
+            .arg(BINDINGS_MODULE)
+            .arg(&snippet_file)
+            .arg(&self.static_lib)
+            .args(["-cclib", "-lpthread", "-cclib", "-lm", "-cclib", "-ldl"])
+            .arg("-o")
+            .arg(&exe);
+        run(cmd)?;
+        let stdout = run(Command::new(&exe))?;
+        std::fs::remove_dir_all(&dir)?;
+        Ok(stdout)
+    }
+
+    /// Runs the snippet and compares its output with `expected`, leading and
+    /// trailing blank lines as well as the common indentation are ignored.
+    pub fn expect(&self, snippet: &str, expected: &str) -> Result<()> {
+        let actual = normalize(&self.run(snippet)?);
+        let expected = normalize(expected);
+        if actual == expected {
+            Ok(())
+        } else {
+            let diff = diff(&expected, &actual);
+            Err(Error::Mismatch { expected, actual, diff })
+        }
+    }
+}
+
+fn run(mut cmd: Command) -> Result<String> {
+    let output = cmd.output()?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(Error::Command {
+            cmd: format!("{:?}", cmd),
+            status: output.status,
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        })
+    }
+}
+
+fn normalize(s: &str) -> String {
+    let lines: Vec<&str> = s.lines().map(|l| l.trim_end()).collect();
+    let start = lines.iter().position(|l| !l.is_empty()).unwrap_or(lines.len());
+    let end = lines.iter().rposition(|l| !l.is_empty()).map_or(start, |e| e + 1);
+    let lines = &lines[start..end];
+    let indent = lines
+        .iter()
+        .filter(|l| !l.is_empty())
+        .map(|l| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let lines: Vec<&str> =
+        lines.iter().map(|l| if l.is_empty() { l } else { &l[indent..] }).collect();
+    lines.join("\n")
+}
+
+// A line based diff using the longest common subsequence, lines that are only
+// expected are prefixed with '-', lines that are only in the output with '+'.
+fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    let (n, m) = (expected.len(), actual.len());
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if expected[i] == actual[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            }
+        }
+    }
+    let mut out = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && expected[i] == actual[j] {
+            out.push(format!(" {}", expected[i]));
+            i += 1;
+            j += 1;
+        } else if j < m && (i == n || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            out.push(format!("+{}", actual[j]));
+            j += 1;
+        } else {
+            out.push(format!("-{}", expected[i]));
+            i += 1;
+        }
+    }
+    out.join("\n")
+}
@@ -23,24 +23,28 @@ impl ToValue for () {
 
 impl ToValue for i32 {
     fn to_value(&self) -> ocaml_sys::Value {
+        crate::borrowed::check_alloc();
         unsafe { ocaml_sys::caml_copy_int32(*self) }
     }
 }
 
 impl ToValue for i64 {
     fn to_value(&self) -> ocaml_sys::Value {
+        crate::borrowed::check_alloc();
         unsafe { ocaml_sys::caml_copy_int64(*self) }
     }
 }
 
 impl ToValue for f32 {
     fn to_value(&self) -> ocaml_sys::Value {
+        crate::borrowed::check_alloc();
         unsafe { ocaml_sys::caml_copy_double(*self as f64) }
     }
 }
 
 impl ToValue for f64 {
     fn to_value(&self) -> ocaml_sys::Value {
+        crate::borrowed::check_alloc();
         unsafe { ocaml_sys::caml_copy_double(*self) }
     }
 }
@@ -164,6 +168,15 @@ where
     }
 }
 
+impl<T> ToValue for &T
+where
+    T: ToValue,
+{
+    fn to_value(&self) -> ocaml_sys::Value {
+        T::to_value(self)
+    }
+}
+
 impl<T, E> ToValue for Result<T, E>
 where
     T: ToValue,
@@ -189,8 +202,49 @@ where
     }
 }
 
+// Maps are converted to association lists and sets to lists, sorted by
+// increasing keys for the BTree versions.
+impl<K, V, S> ToValue for std::collections::HashMap<K, V, S>
+where
+    K: ToValue,
+    V: ToValue,
+{
+    fn to_value(&self) -> ocaml_sys::Value {
+        crate::list::rev_list_to_value(self.iter())
+    }
+}
+
+impl<K, V> ToValue for std::collections::BTreeMap<K, V>
+where
+    K: ToValue,
+    V: ToValue,
+{
+    fn to_value(&self) -> ocaml_sys::Value {
+        crate::list::rev_list_to_value(self.iter().rev())
+    }
+}
+
+impl<T, S> ToValue for std::collections::HashSet<T, S>
+where
+    T: ToValue,
+{
+    fn to_value(&self) -> ocaml_sys::Value {
+        crate::list::rev_list_to_value(self.iter())
+    }
+}
+
+impl<T> ToValue for std::collections::BTreeSet<T>
+where
+    T: ToValue,
+{
+    fn to_value(&self) -> ocaml_sys::Value {
+        crate::list::rev_list_to_value(self.iter().rev())
+    }
+}
+
 impl ToValue for Vec<u8> {
     fn to_value(&self) -> ocaml_sys::Value {
+        crate::borrowed::check_alloc();
         let value = unsafe { ocaml_sys::caml_alloc_string(self.len()) };
         let content_ptr = unsafe { ocaml_sys::string_val(value) };
         unsafe { std::ptr::copy_nonoverlapping(self.as_ptr(), content_ptr, self.len()) };
@@ -200,6 +254,7 @@ impl ToValue for Vec<u8> {
 
 impl ToValue for String {
     fn to_value(&self) -> ocaml_sys::Value {
+        crate::borrowed::check_alloc();
         let value = unsafe { ocaml_sys::caml_alloc_string(self.len()) };
         let content_ptr = unsafe { ocaml_sys::string_val(value) };
         unsafe { std::ptr::copy_nonoverlapping(self.as_ptr(), content_ptr, self.len()) };
@@ -207,6 +262,27 @@ impl ToValue for String {
     }
 }
 
+#[cfg(unix)]
+impl ToValue for std::ffi::OsString {
+    fn to_value(&self) -> ocaml_sys::Value {
+        use std::os::unix::ffi::OsStrExt;
+        self.as_bytes().to_vec().to_value()
+    }
+}
+
+#[cfg(not(unix))]
+impl ToValue for std::ffi::OsString {
+    fn to_value(&self) -> ocaml_sys::Value {
+        self.to_string_lossy().into_owned().to_value()
+    }
+}
+
+impl ToValue for std::path::PathBuf {
+    fn to_value(&self) -> ocaml_sys::Value {
+        self.as_os_str().to_os_string().to_value()
+    }
+}
+
 impl<T> ToValue for RootedValue<T> {
     fn to_value(&self) -> ocaml_sys::Value {
         self.value().value
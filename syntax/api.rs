@@ -27,14 +27,109 @@ pub enum Type {
     VecArray(Box<Type>),
     VecList(Box<Type>),
     RustResult(Box<Type>),
-    BigArray1(Box<Type>),
+    // OCaml exceptions are re-raised, so this is not visible in the OCaml type.
+    OCamlResult(Box<Type>),
+    // The element type and whether the layout is Fortran rather than C.
+    BigArray(BigArrayDims, Box<Type>, bool),
     Option(Box<Type>),
     Result(Box<Type>, Box<Type>),
     Fn0(Box<Type>),
-    Fn1(Box<Type>, Box<Type>),
+    // Closures with at least one argument, Fn1 to FnN.
+    FnN(Vec<Type>, Box<Type>),
+    // Rust closures returned to OCaml.
+    RustFn1(Box<Type>, Box<Type>),
+    // HashMap and BTreeMap, converted to association lists. The flag is true
+    // for the hashed version.
+    Map(Box<Type>, Box<Type>, bool),
+    // HashSet and BTreeSet, converted to lists.
+    Set(Box<Type>, bool),
+    // Bridged structs and enums applied to type arguments, e.g. Tagged<isize>.
+    Generic(proc_macro2::Ident, Vec<Type>),
+    // Type parameters of a generic struct or enum definition.
+    #[allow(dead_code)]
+    Param(proc_macro2::Ident),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BigArrayDims {
+    Array1,
+    Array2,
+    Array3,
+    Genarray,
+}
+
+impl BigArrayDims {
+    fn of_ident(ident: &proc_macro2::Ident) -> Option<Self> {
+        match ident.to_string().as_str() {
+            "BigArray1" => Some(Self::Array1),
+            "BigArray2" => Some(Self::Array2),
+            "BigArray3" => Some(Self::Array3),
+            "Genarray" => Some(Self::Genarray),
+            _ => None,
+        }
+    }
+
+    fn ocaml_module(&self) -> &'static str {
+        match self {
+            Self::Array1 => "Array1",
+            Self::Array2 => "Array2",
+            Self::Array3 => "Array3",
+            Self::Genarray => "Genarray",
+        }
+    }
+}
+
+/// The OCaml type and bigarray element type for the Rust types implementing
+/// ocaml_rust::bigarray::Elem.
+fn bigarray_elt(ident: &str) -> Option<(&'static str, &'static str)> {
+    let elt = match ident {
+        "f16" => ("float", "float16_elt"),
+        "f32" => ("float", "float32_elt"),
+        "f64" => ("float", "float64_elt"),
+        "i8" => ("int", "int8_signed_elt"),
+        "u8" => ("int", "int8_unsigned_elt"),
+        "i16" => ("int", "int16_signed_elt"),
+        "u16" => ("int", "int16_unsigned_elt"),
+        "i32" => ("int32", "int32_elt"),
+        "i64" => ("int64", "int64_elt"),
+        "isize" => ("int", "int_elt"),
+        "NativeInt" => ("nativeint", "nativeint_elt"),
+        "Complex32" => ("Complex.t", "complex32_elt"),
+        "Complex64" => ("Complex.t", "complex64_elt"),
+        "OCamlChar" => ("char", "int8_unsigned_elt"),
+        _ => return None,
+    };
+    Some(elt)
+}
+
+/// The largest N for which ocaml_rust::closure defines FnN.
+const MAX_CLOSURE_ARITY: usize = 8;
+
+/// Returns N for identifiers of the form FnN with N at least 1.
+fn closure_arity(ident: &proc_macro2::Ident) -> Option<usize> {
+    let ident = ident.to_string();
+    let digits = ident.strip_prefix("Fn")?;
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok().filter(|&n| n > 0)
 }
 
 impl Type {
+    fn bigarray<T: ToTokens>(
+        dims: BigArrayDims,
+        elem: Type,
+        fortran: bool,
+        tokens: T,
+    ) -> Result<Self> {
+        match &elem {
+            Type::Ident(ident) if bigarray_elt(&ident.to_string()).is_some() => {
+                Ok(Type::BigArray(dims, Box::new(elem), fortran))
+            }
+            _ => Err(Error::new_spanned(tokens, "unsupported bigarray element type")),
+        }
+    }
+
     // It would be nice for this to be extensible and not requiring introducing
     // new matched cases for supporting new types.
     pub fn parse_type(ty: &syn::Type) -> Result<Type> {
@@ -46,6 +141,35 @@ impl Type {
                     let ident = segment.ident.clone();
                     match &segment.arguments {
                         syn::PathArguments::None => return Ok(Type::Ident(ident)),
+                        syn::PathArguments::AngleBracketed(
+                            syn::AngleBracketedGenericArguments { args, .. },
+                        ) if closure_arity(&ident).is_some() => {
+                            let arity = closure_arity(&ident).unwrap();
+                            if arity > MAX_CLOSURE_ARITY {
+                                return Err(Error::new_spanned(
+                                    ty,
+                                    format!(
+                                        "closures with more than {} arguments are not supported",
+                                        MAX_CLOSURE_ARITY
+                                    ),
+                                ));
+                            }
+                            if args.len() != arity + 1 {
+                                return Err(Error::new_spanned(
+                                    ty,
+                                    format!("{} expects {} type arguments", ident, arity + 1),
+                                ));
+                            }
+                            let mut tys = args
+                                .iter()
+                                .map(|arg| match arg {
+                                    syn::GenericArgument::Type(ty) => Self::parse_type(ty),
+                                    arg => Err(Error::new_spanned(arg, "expected a type argument")),
+                                })
+                                .collect::<Result<Vec<_>>>()?;
+                            let res = tys.pop().unwrap();
+                            return Ok(Type::FnN(tys, Box::new(res)));
+                        }
                         syn::PathArguments::AngleBracketed(
                             syn::AngleBracketedGenericArguments { args, .. },
                         ) if args.len() == 1 => {
@@ -63,8 +187,11 @@ impl Type {
                                 if ident == "RustResult" {
                                     return Ok(Type::RustResult(Box::new(ty)));
                                 }
-                                if ident == "BigArray1" {
-                                    return Ok(Type::BigArray1(Box::new(ty)));
+                                if ident == "OCamlResult" {
+                                    return Ok(Type::OCamlResult(Box::new(ty)));
+                                }
+                                if let Some(dims) = BigArrayDims::of_ident(&ident) {
+                                    return Type::bigarray(dims, ty, false, &args[0]);
                                 }
                                 if ident == "Fn0" {
                                     return Ok(Type::Fn0(Box::new(ty)));
@@ -72,6 +199,9 @@ impl Type {
                                 if ident == "Box" {
                                     return Ok(ty);
                                 }
+                                if ident == "HashSet" || ident == "BTreeSet" {
+                                    return Ok(Type::Set(Box::new(ty), ident == "HashSet"));
+                                }
                             }
                         }
                         syn::PathArguments::AngleBracketed(
@@ -84,8 +214,24 @@ impl Type {
                                     if ident == "Result" {
                                         return Ok(Type::Result(ty0, ty1));
                                     }
-                                    if ident == "Fn1" {
-                                        return Ok(Type::Fn1(ty0, ty1));
+                                    if ident == "RustFn1" {
+                                        return Ok(Type::RustFn1(ty0, ty1));
+                                    }
+                                    if ident == "HashMap" || ident == "BTreeMap" {
+                                        return Ok(Type::Map(ty0, ty1, ident == "HashMap"));
+                                    }
+                                    if let (Some(dims), Type::Ident(layout)) =
+                                        (BigArrayDims::of_ident(&ident), ty1.as_ref())
+                                    {
+                                        match layout.to_string().as_str() {
+                                            "CLayout" => {
+                                                return Type::bigarray(dims, *ty0, false, &args[0])
+                                            }
+                                            "FortranLayout" => {
+                                                return Type::bigarray(dims, *ty0, true, &args[0])
+                                            }
+                                            _ => {}
+                                        }
                                     }
                                 }
                             }
@@ -94,13 +240,36 @@ impl Type {
                         syn::PathArguments::AngleBracketed(_)
                         | syn::PathArguments::Parenthesized(_) => {}
                     }
+                    if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                        let args = args
+                            .args
+                            .iter()
+                            .map(|arg| match arg {
+                                syn::GenericArgument::Type(ty) => Self::parse_type(ty),
+                                arg => Err(Error::new_spanned(arg, "expected a type argument")),
+                            })
+                            .collect::<Result<Vec<_>>>()?;
+                        return Ok(Type::Generic(ident, args));
+                    }
                 }
             }
             syn::Type::Tuple(tuple) => {
                 let v: Result<Vec<Self>> = tuple.elems.iter().map(Self::parse_type).collect();
                 return Ok(Self::Tuple(v?));
             }
-            syn::Type::Reference(type_reference) => return Self::parse_type(&type_reference.elem),
+            syn::Type::Reference(type_reference) => {
+                // OCaml bytes are the mutable version of strings.
+                if let (Some(_), syn::Type::Slice(slice)) =
+                    (type_reference.mutability, type_reference.elem.as_ref())
+                {
+                    if let Type::Ident(ident) = Type::parse_type(&slice.elem)? {
+                        if ident == "u8" {
+                            return Ok(Type::Ident(Ident::new("Bytes", ident.span())));
+                        }
+                    }
+                }
+                return Self::parse_type(&type_reference.elem);
+            }
             syn::Type::Slice(slice) => {
                 let ty = Type::parse_type(&slice.elem)?;
                 return Ok(Type::VecArray(Box::new(ty)));
@@ -110,6 +279,132 @@ impl Type {
         Err(Error::new_spanned(ty, format!("unsupported type {}", ty.to_token_stream())))
     }
 
+    /// Parses the type of a field in a struct or enum definition, identifiers
+    /// matching one of the type parameters of the definition become OCaml type
+    /// variables.
+    #[allow(dead_code)]
+    pub fn parse_field_type(ty: &syn::Type, generics: &syn::Generics) -> Result<Type> {
+        let params: Vec<&proc_macro2::Ident> =
+            generics.type_params().map(|param| &param.ident).collect();
+        Ok(Self::parse_type(ty)?.bind_params(&params))
+    }
+
+    #[allow(dead_code)]
+    fn bind_params(self, params: &[&proc_macro2::Ident]) -> Type {
+        let bind = |ty: Box<Type>| Box::new(ty.bind_params(params));
+        let bind_all = |tys: Vec<Type>| tys.into_iter().map(|ty| ty.bind_params(params)).collect();
+        match self {
+            Self::Ident(ident) if params.contains(&&ident) => Self::Param(ident),
+            Self::Unit | Self::Ident(_) | Self::Param(_) => self,
+            Self::Tuple(tys) => Self::Tuple(bind_all(tys)),
+            Self::VecArray(ty) => Self::VecArray(bind(ty)),
+            Self::VecList(ty) => Self::VecList(bind(ty)),
+            Self::RustResult(ty) => Self::RustResult(bind(ty)),
+            Self::OCamlResult(ty) => Self::OCamlResult(bind(ty)),
+            Self::BigArray(dims, ty, fortran) => Self::BigArray(dims, bind(ty), fortran),
+            Self::Option(ty) => Self::Option(bind(ty)),
+            Self::Result(ty_ok, ty_err) => Self::Result(bind(ty_ok), bind(ty_err)),
+            Self::Fn0(ty) => Self::Fn0(bind(ty)),
+            Self::FnN(ty_args, ty_res) => Self::FnN(bind_all(ty_args), bind(ty_res)),
+            Self::RustFn1(ty_arg, ty_res) => Self::RustFn1(bind(ty_arg), bind(ty_res)),
+            Self::Map(ty_key, ty_value, hashed) => Self::Map(bind(ty_key), bind(ty_value), hashed),
+            Self::Set(ty, hashed) => Self::Set(bind(ty), hashed),
+            Self::Generic(ident, tys) => Self::Generic(ident, bind_all(tys)),
+        }
+    }
+
+    /// Checks that the types applied to type arguments are generic structs or
+    /// enums declared in the bridge, `generics` maps their names to their
+    /// number of type parameters.
+    fn check_generics(&self, generics: &[(String, usize)]) -> Result<()> {
+        let check_all = |tys: &[Type]| tys.iter().try_for_each(|ty| ty.check_generics(generics));
+        match self {
+            Self::Unit | Self::Ident(_) | Self::Param(_) => Ok(()),
+            Self::Tuple(tys) => check_all(tys),
+            Self::VecArray(ty)
+            | Self::VecList(ty)
+            | Self::RustResult(ty)
+            | Self::OCamlResult(ty)
+            | Self::BigArray(_, ty, _)
+            | Self::Option(ty)
+            | Self::Fn0(ty)
+            | Self::Set(ty, _) => ty.check_generics(generics),
+            Self::Result(ty1, ty2) | Self::RustFn1(ty1, ty2) | Self::Map(ty1, ty2, _) => {
+                ty1.check_generics(generics)?;
+                ty2.check_generics(generics)
+            }
+            Self::FnN(ty_args, ty_res) => {
+                check_all(ty_args)?;
+                ty_res.check_generics(generics)
+            }
+            Self::Generic(ident, tys) => {
+                // Generic types defined outside of the bridge, e.g. deriving
+                // ToValue, are checked by the Rust and OCaml compilers.
+                match generics.iter().find(|(name, _)| ident == name) {
+                    Some((_, nparams)) if *nparams != tys.len() => {
+                        let msg = format!("{} expects {} type arguments", ident, nparams);
+                        return Err(Error::new(ident.span(), msg));
+                    }
+                    Some(_) | None => {}
+                }
+                check_all(tys)
+            }
+        }
+    }
+
+    /// Returns the first type satisfying `f` among this type and its components.
+    pub fn find(&self, f: &dyn Fn(&Type) -> bool) -> Option<&Type> {
+        if f(self) {
+            return Some(self);
+        }
+        match self {
+            Self::Unit | Self::Ident(_) | Self::Param(_) => None,
+            Self::Tuple(tys) | Self::Generic(_, tys) => tys.iter().find_map(|ty| ty.find(f)),
+            Self::VecArray(ty)
+            | Self::VecList(ty)
+            | Self::RustResult(ty)
+            | Self::OCamlResult(ty)
+            | Self::BigArray(_, ty, _)
+            | Self::Option(ty)
+            | Self::Fn0(ty)
+            | Self::Set(ty, _) => ty.find(f),
+            Self::Result(ty1, ty2) | Self::RustFn1(ty1, ty2) | Self::Map(ty1, ty2, _) => {
+                ty1.find(f).or_else(|| ty2.find(f))
+            }
+            Self::FnN(ty_args, ty_res) => {
+                ty_args.iter().find_map(|ty| ty.find(f)).or_else(|| ty_res.find(f))
+            }
+        }
+    }
+
+    pub fn unboxed(&self) -> Option<Unboxed> {
+        match self {
+            Self::Unit => Some(Unboxed::Unit),
+            Self::Ident(ident) => match ident.to_string().as_str() {
+                "f64" => Some(Unboxed::Float),
+                "i64" => Some(Unboxed::Int64),
+                "i32" => Some(Unboxed::Int32),
+                "isize" => Some(Unboxed::Int),
+                "bool" => Some(Unboxed::Bool),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// The OCaml type of a function returning this type, this only differs from
+    /// `to_ocaml_string` for `RustResult` when a `rust_error` mode is set.
+    #[allow(dead_code)]
+    pub fn to_ocaml_output_string(&self, rust_error: ErrorMode) -> String {
+        match (self, rust_error) {
+            (Self::RustResult(ty), ErrorMode::Record) => {
+                format!("({}, Rust_error.t) Result.t", ty.to_ocaml_string())
+            }
+            (Self::RustResult(ty), ErrorMode::Exception) => ty.to_ocaml_string(),
+            _ => self.to_ocaml_string(),
+        }
+    }
+
     #[allow(dead_code)]
     pub fn to_ocaml_string(&self) -> String {
         match self {
@@ -121,6 +416,9 @@ impl Type {
                 "i64" => "Int64.t".to_string(),
                 "f32" | "f64" => "float".to_string(),
                 "u8" => "char".to_string(),
+                "str" | "OsString" | "PathBuf" => "string".to_string(),
+                "OCamlInt" => "int".to_string(),
+                "FloatArray" => "float array".to_string(),
                 ident => ocamlize(ident),
             },
             Self::Tuple(tuple) => {
@@ -134,28 +432,32 @@ impl Type {
             Self::Option(ty) => {
                 format!("{} option", ty.to_ocaml_string())
             }
-            Self::VecArray(ty) => {
-                format!("{} array", ty.to_ocaml_string())
-            }
+            // Vec<u8> and &[u8] are converted to and from strings.
+            Self::VecArray(ty) => match ty.as_ref() {
+                Self::Ident(ident) if ident == "u8" => "string".to_string(),
+                _ => format!("{} array", ty.to_ocaml_string()),
+            },
             Self::VecList(ty) => {
                 format!("{} list", ty.to_ocaml_string())
             }
             Self::RustResult(ty) => {
                 format!("({}, string) Result.t", ty.to_ocaml_string())
             }
-            Self::BigArray1(ty) => {
+            Self::OCamlResult(ty) => ty.to_ocaml_string(),
+            Self::BigArray(dims, ty, fortran) => {
                 let (ocaml_type, elt_type) = match ty.as_ref() {
-                    Self::Ident(ident) => match ident.to_string().as_str() {
-                        "f64" => ("float".to_string(), "Bigarray.float64_elt".to_string()),
-                        "f32" => ("float".to_string(), "Bigarray.float32_elt".to_string()),
-                        "i64" => ("int".to_string(), "Bigarray.int64_elt".to_string()),
-                        "i32" => ("int".to_string(), "Bigarray.int32_elt".to_string()),
-                        "u8" => ("char".to_string(), "Bigarray.int8_unsigned_elt".to_string()),
-                        ident => (ocamlize(ident), ocamlize(ident)),
-                    },
+                    Self::Ident(ident) => bigarray_elt(&ident.to_string())
+                        .unwrap_or_else(|| panic!("unexpected type nested in bigarray {:?}", self)),
                     _ => panic!("unexpected type nested in bigarray {:?}", self),
                 };
-                format!("({}, {}, Bigarray.c_layout) Bigarray.Array1.t", ocaml_type, elt_type)
+                let layout = if *fortran { "fortran_layout" } else { "c_layout" };
+                format!(
+                    "({}, Bigarray.{}, Bigarray.{}) Bigarray.{}.t",
+                    ocaml_type,
+                    elt_type,
+                    layout,
+                    dims.ocaml_module()
+                )
             }
             Self::Result(ty_ok, ty_err) => {
                 format!("({}, {}) Result.t", ty_ok.to_ocaml_string(), ty_err.to_ocaml_string())
@@ -163,9 +465,56 @@ impl Type {
             Self::Fn0(ty) => {
                 format!("(unit -> ({}))", ty.to_ocaml_string())
             }
-            Self::Fn1(ty_arg, ty_res) => {
-                format!("(({}) -> ({}))", ty_arg.to_ocaml_string(), ty_res.to_ocaml_string())
+            Self::FnN(ty_args, ty_res) => {
+                let args: Vec<_> =
+                    ty_args.iter().map(|ty| format!("({})", ty.to_ocaml_string())).collect();
+                format!("({} -> ({}))", args.join(" -> "), ty_res.to_ocaml_string())
+            }
+            // This uses a type alias rather than an arrow type as the arity of
+            // externals is computed syntactically.
+            Self::RustFn1(ty_arg, ty_res) => {
+                format!("(({}), ({})) rust_fn1", ty_arg.to_ocaml_string(), ty_res.to_ocaml_string())
             }
+            Self::Map(ty_key, ty_value, _) => {
+                format!("({} * {}) list", ty_key.to_ocaml_string(), ty_value.to_ocaml_string())
+            }
+            Self::Set(ty, _) => format!("{} list", ty.to_ocaml_string()),
+            Self::Generic(ident, tys) => {
+                let args: Vec<_> = tys.iter().map(|ty| ty.to_ocaml_string()).collect();
+                let ident = ocamlize(&ident.to_string());
+                if args.len() == 1 {
+                    format!("{} {}", args[0], ident)
+                } else {
+                    format!("({}) {}", args.join(", "), ident)
+                }
+            }
+            Self::Param(ident) => format!("'{}", ocamlize(&ident.to_string())),
+        }
+    }
+}
+
+/// Scalar representations that can be passed to native stubs without boxing,
+/// see https://ocaml.org/manual/intfc.html#s:C-cheaper-call
+#[derive(Debug, Clone, Copy)]
+pub enum Unboxed {
+    Float,
+    Int64,
+    Int32,
+    Int,
+    Bool,
+    Unit,
+}
+
+impl Unboxed {
+    #[allow(dead_code)]
+    pub fn to_ocaml_string(self) -> String {
+        match self {
+            Self::Float => "(float [@unboxed])".to_string(),
+            Self::Int64 => "(Int64.t [@unboxed])".to_string(),
+            Self::Int32 => "(Int32.t [@unboxed])".to_string(),
+            Self::Int => "(int [@untagged])".to_string(),
+            Self::Bool => "bool".to_string(),
+            Self::Unit => "unit".to_string(),
         }
     }
 }
@@ -198,26 +547,96 @@ impl Parse for Namespace {
     }
 }
 
+/// How the error case of a `RustResult` is exposed on the OCaml side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorMode {
+    /// `('a, string) Result.t`, this is the default.
+    String,
+    /// `('a, Rust_error.t) Result.t`.
+    Record,
+    /// `'a`, raising the `Rust_error` exception on errors.
+    Exception,
+}
+
+impl Parse for ErrorMode {
+    fn parse(input: ParseStream) -> Result<Self> {
+        input.parse::<Token![=]>()?;
+        let lit_str: syn::LitStr = input.parse()?;
+        match lit_str.value().as_str() {
+            "string" => Ok(Self::String),
+            "record" => Ok(Self::Record),
+            "exception" => Ok(Self::Exception),
+            mode => {
+                Err(Error::new(lit_str.span(), format!("unsupported rust_error mode {}", mode)))
+            }
+        }
+    }
+}
+
+/// Arguments of type `&str`, `&FloatArray`, `&[T]` or `&mut [T]` borrow directly from the OCaml
+/// value rather than being copied, returns whether the borrow is mutable.
+pub fn borrowed_arg(ty: &syn::Type) -> Option<bool> {
+    match ty {
+        syn::Type::Reference(reference) => match reference.elem.as_ref() {
+            syn::Type::Slice(_) => Some(reference.mutability.is_some()),
+            syn::Type::Path(path)
+                if path.qself.is_none()
+                    && (path.path.is_ident("str") || path.path.is_ident("FloatArray")) =>
+            {
+                Some(reference.mutability.is_some())
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
 pub struct Attrs {
     pub namespace: Option<Vec<String>>,
     pub release_runtime_lock: bool,
+    pub noalloc: bool,
+    pub rust_error: ErrorMode,
+    pub strict_utf8: bool,
+    pub docs: Vec<String>,
+    pub deprecated: Option<String>,
 }
 
 impl Attrs {
     fn parse(attrs: Vec<Attribute>) -> Result<Self> {
         let mut namespace = None;
         let mut release_runtime_lock = false;
+        let mut noalloc = false;
+        let mut rust_error = ErrorMode::String;
+        let mut strict_utf8 = false;
+        let docs = doc_lines(&attrs);
+        let deprecated = deprecated_note(&attrs);
         for attr in attrs.into_iter() {
-            if attr.path.is_ident("namespace") {
+            if attr.path.is_ident("doc") || attr.path.is_ident("deprecated") {
+                continue;
+            } else if attr.path.is_ident("namespace") {
                 let value: Namespace = syn::parse2(attr.tokens)?;
                 namespace = Some(value.0.split("::").map(String::from).collect())
             } else if attr.path.is_ident("release_runtime_lock") {
                 release_runtime_lock = true;
+            } else if attr.path.is_ident("noalloc") {
+                noalloc = true;
+            } else if attr.path.is_ident("rust_error") {
+                rust_error = syn::parse2(attr.tokens)?;
+            } else if attr.path.is_ident("strict_utf8") {
+                strict_utf8 = true;
             } else {
                 return Err(Error::new_spanned(attr, "unsupported attribute"));
             }
         }
-        Ok(Attrs { namespace, release_runtime_lock })
+        Ok(Attrs {
+            namespace,
+            release_runtime_lock,
+            noalloc,
+            rust_error,
+            strict_utf8,
+            docs,
+            deprecated,
+        })
     }
 }
 
@@ -230,6 +649,19 @@ pub enum ModItem {
     },
 }
 
+impl ModItem {
+    /// Returns the unboxed representation of the arguments and of the output
+    /// when all of them are scalars.
+    pub fn unboxed_signature(&self) -> Option<(Vec<Unboxed>, Unboxed)> {
+        match self {
+            ModItem::Fn { args, output, .. } => {
+                let args: Option<Vec<_>> = args.iter().map(|(_, _, ty)| ty.unboxed()).collect();
+                Some((args?, output.1.unboxed()?))
+            }
+        }
+    }
+}
+
 pub enum ApiItem {
     ForeignMod { attrs: Vec<Attribute>, lang: Lang, brace_token: token::Brace, items: Vec<ModItem> },
     Enum(syn::ItemEnum),
@@ -254,10 +686,122 @@ impl Parse for Api {
         while !content.is_empty() {
             api_items.push(content.parse()?);
         }
+        check_generics(&api_items)?;
+        check_unboxed_floats(&api_items)?;
         Ok(Api { ident, api_items })
     }
 }
 
+fn check_generics(api_items: &[ApiItem]) -> Result<()> {
+    let generics: Vec<(String, usize)> = api_items
+        .iter()
+        .filter_map(|api_item| {
+            let (ident, generics) = match api_item {
+                ApiItem::Enum(e) => (&e.ident, &e.generics),
+                ApiItem::Struct(s) => (&s.ident, &s.generics),
+                _ => return None,
+            };
+            let nparams = generics.type_params().count();
+            if nparams == 0 {
+                None
+            } else {
+                Some((ident.to_string(), nparams))
+            }
+        })
+        .collect();
+    let check_fields = |fields: &syn::Fields, item_generics: &syn::Generics| {
+        fields.iter().try_for_each(|field| {
+            Type::parse_field_type(&field.ty, item_generics)?.check_generics(&generics)
+        })
+    };
+    for api_item in api_items.iter() {
+        match api_item {
+            ApiItem::ForeignMod { items, .. } => {
+                for item in items.iter() {
+                    let ModItem::Fn { args, output, .. } = item;
+                    for (_, _, ty) in args.iter() {
+                        ty.check_generics(&generics)?
+                    }
+                    output.1.check_generics(&generics)?
+                }
+            }
+            ApiItem::Enum(e) => {
+                for variant in e.variants.iter() {
+                    check_fields(&variant.fields, &e.generics)?
+                }
+            }
+            ApiItem::Struct(s) => check_fields(&s.fields, &s.generics)?,
+            ApiItem::Type(_) | ApiItem::Include(_) | ApiItem::Other(_) => {}
+        }
+    }
+    Ok(())
+}
+
+// OCaml arrays of unboxed float newtypes use the flat float array layout, the
+// conversion only supports this for Vec<f64> and Vec<f32>.
+fn check_unboxed_floats(api_items: &[ApiItem]) -> Result<()> {
+    let floats: Vec<&Ident> = api_items
+        .iter()
+        .filter_map(|api_item| match api_item {
+            ApiItem::Struct(s) if struct_is_unboxed(s).unwrap_or(false) => {
+                match &s.fields.iter().next()?.ty {
+                    syn::Type::Path(p) if p.path.is_ident("f64") || p.path.is_ident("f32") => {
+                        Some(&s.ident)
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        })
+        .collect();
+    if floats.is_empty() {
+        return Ok(());
+    }
+    let check = |ty: &Type| {
+        let float_array = ty.find(&|ty| match ty {
+            Type::VecArray(ty) => matches!(ty.as_ref(), Type::Ident(i) if floats.contains(&i)),
+            _ => false,
+        });
+        match float_array {
+            Some(Type::VecArray(ty)) => {
+                let Type::Ident(ident) = ty.as_ref() else { unreachable!() };
+                let msg = format!(
+                    "arrays of the unboxed float newtype {} are not supported, use Vec<f64> instead",
+                    ident
+                );
+                Err(Error::new(ident.span(), msg))
+            }
+            _ => Ok(()),
+        }
+    };
+    let check_fields = |fields: &syn::Fields, item_generics: &syn::Generics| {
+        fields
+            .iter()
+            .try_for_each(|field| check(&Type::parse_field_type(&field.ty, item_generics)?))
+    };
+    for api_item in api_items.iter() {
+        match api_item {
+            ApiItem::ForeignMod { items, .. } => {
+                for item in items.iter() {
+                    let ModItem::Fn { args, output, .. } = item;
+                    for (_, _, ty) in args.iter() {
+                        check(ty)?
+                    }
+                    check(&output.1)?
+                }
+            }
+            ApiItem::Enum(e) => {
+                for variant in e.variants.iter() {
+                    check_fields(&variant.fields, &e.generics)?
+                }
+            }
+            ApiItem::Struct(s) => check_fields(&s.fields, &s.generics)?,
+            ApiItem::Type(_) | ApiItem::Include(_) | ApiItem::Other(_) => {}
+        }
+    }
+    Ok(())
+}
+
 impl Parse for ApiItem {
     fn parse(input: ParseStream) -> Result<Self> {
         let attrs = input.call(Attribute::parse_outer)?;
@@ -282,6 +826,7 @@ impl Parse for ApiItem {
             }
             syn::Item::ForeignMod(mut item) => {
                 item.attrs.splice(..0, attrs);
+                let lang = Lang::of_abi(&item.abi)?;
                 let mut mod_items = vec![];
                 for item in item.items.into_iter() {
                     match item {
@@ -315,16 +860,77 @@ impl Parse for ApiItem {
                                     (Box::new(syn::Type::Verbatim(quote! { () })), Type::Unit)
                                 }
                                 syn::ReturnType::Type(_arrow, type_) => {
+                                    if let syn::Type::Reference(_) = type_.as_ref() {
+                                        return Err(Error::new(
+                                            type_.span(),
+                                            "references cannot be returned, borrowed OCaml values are only valid during the call",
+                                        ));
+                                    }
                                     let ty = Type::parse_type(type_)?;
                                     (type_.clone(), ty)
                                 }
                             };
-                            mod_items.push(ModItem::Fn {
-                                ident: f.sig.ident,
-                                args,
-                                output,
-                                attrs: Attrs::parse(f.attrs)?,
-                            })
+                            let attrs = Attrs::parse(f.attrs)?;
+                            if let Lang::OCaml = lang {
+                                if attrs.namespace.is_some()
+                                    || attrs.release_runtime_lock
+                                    || attrs.noalloc
+                                    || attrs.rust_error != ErrorMode::String
+                                    || attrs.strict_utf8
+                                {
+                                    return Err(Error::new(
+                                        f.sig.ident.span(),
+                                        "unsupported attribute in extern \"OCaml\"",
+                                    ));
+                                }
+                                if let Some((ident, _, _)) =
+                                    args.iter().find(|(_, ty, _)| borrowed_arg(ty).is_some())
+                                {
+                                    return Err(Error::new(
+                                        ident.ident.span(),
+                                        "borrowed slices cannot be passed to extern \"OCaml\" functions, use an owned String or Vec instead",
+                                    ));
+                                }
+                            }
+                            if attrs.rust_error != ErrorMode::String
+                                && !matches!(output.1, Type::RustResult(_))
+                            {
+                                return Err(Error::new(
+                                    f.sig.ident.span(),
+                                    "rust_error requires a RustResult return type",
+                                ));
+                            }
+                            let borrowed = args.iter().any(|(_, ty, _)| borrowed_arg(ty).is_some());
+                            if attrs.release_runtime_lock && borrowed {
+                                return Err(Error::new(
+                                    f.sig.ident.span(),
+                                    "borrowed slices cannot be used with release_runtime_lock",
+                                ));
+                            }
+                            // Bigarrays hold a root which has to be created and dropped with
+                            // the lock held, this includes bigarrays nested in other types.
+                            let is_bigarray = |ty: &Type| matches!(ty, Type::BigArray(..));
+                            let owned_bigarray = args.iter().any(|(_, ty, typ)| {
+                                typ.find(&is_bigarray).is_some()
+                                    && !matches!(ty.as_ref(), syn::Type::Reference(_))
+                            });
+                            if attrs.release_runtime_lock
+                                && (owned_bigarray || output.1.find(&is_bigarray).is_some())
+                            {
+                                return Err(Error::new(
+                                    f.sig.ident.span(),
+                                    "bigarrays must be passed by reference and cannot be returned with release_runtime_lock",
+                                ));
+                            }
+                            let all_unboxed = output.1.unboxed().is_some()
+                                && args.iter().all(|(_, _, ty)| ty.unboxed().is_some());
+                            if attrs.noalloc && (attrs.release_runtime_lock || !all_unboxed) {
+                                return Err(Error::new(
+                                    f.sig.ident.span(),
+                                    "noalloc requires a signature using only scalar types",
+                                ));
+                            }
+                            mod_items.push(ModItem::Fn { ident: f.sig.ident, args, output, attrs })
                         }
                         _ => {
                             return Err(Error::new(item.span(), "unsupported in extern mod"));
@@ -333,7 +939,7 @@ impl Parse for ApiItem {
                 }
                 Ok(ApiItem::ForeignMod {
                     attrs: item.attrs,
-                    lang: Lang::of_abi(&item.abi)?,
+                    lang,
                     brace_token: item.brace_token,
                     items: mod_items,
                 })
@@ -343,6 +949,97 @@ impl Parse for ApiItem {
     }
 }
 
+/// The lines of the `///` doc comments, these are stored as `#[doc = "..."]`
+/// attributes.
+pub fn doc_lines(attrs: &[Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(syn::Meta::NameValue(syn::MetaNameValue { lit: syn::Lit::Str(s), .. })) => {
+                let line = s.value();
+                Some(line.strip_prefix(' ').unwrap_or(&line).trim_end().to_string())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// The note from a `#[deprecated]` attribute, this is empty if the attribute
+/// does not specify a note.
+pub fn deprecated_note(attrs: &[Attribute]) -> Option<String> {
+    let attr = attrs.iter().find(|attr| attr.path.is_ident("deprecated"))?;
+    let note = match attr.parse_meta() {
+        Ok(syn::Meta::NameValue(syn::MetaNameValue { lit: syn::Lit::Str(s), .. })) => s.value(),
+        Ok(syn::Meta::List(list)) => list
+            .nested
+            .iter()
+            .find_map(|nested| match nested {
+                syn::NestedMeta::Meta(syn::Meta::NameValue(nv)) if nv.path.is_ident("note") => {
+                    match &nv.lit {
+                        syn::Lit::Str(s) => Some(s.value()),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            })
+            .unwrap_or_default(),
+        _ => String::new(),
+    };
+    Some(note)
+}
+
 pub fn attr_is_ocaml_deriving(attr: &Attribute) -> bool {
     !attr.path.segments.is_empty() && attr.path.segments[0].ident == "ocaml_deriving"
 }
+
+/// `#[ocaml_unboxed]` on a newtype struct uses the representation of the inner
+/// type on the OCaml side.
+pub fn attr_is_ocaml_unboxed(attr: &Attribute) -> bool {
+    attr.path.is_ident("ocaml_unboxed")
+}
+
+/// `#[ocaml(..)]` attributes set options for the OCaml side of a type, only
+/// `#[ocaml(polymorphic_variant)]` on enums is supported for now.
+pub fn attr_is_ocaml(attr: &Attribute) -> bool {
+    attr.path.is_ident("ocaml")
+}
+
+/// Returns whether an enum should be mapped to a polymorphic variant, this
+/// checks that no constructor has named fields.
+pub fn enum_is_polymorphic_variant(item: &syn::ItemEnum) -> Result<bool> {
+    let mut polymorphic_variant = false;
+    for attr in item.attrs.iter().filter(|attr| attr_is_ocaml(attr)) {
+        let option: Ident = attr.parse_args()?;
+        if option != "polymorphic_variant" {
+            return Err(Error::new_spanned(option, "unsupported ocaml attribute"));
+        }
+        polymorphic_variant = true;
+    }
+    if polymorphic_variant {
+        for variant in item.variants.iter() {
+            if let syn::Fields::Named(_) = variant.fields {
+                return Err(Error::new_spanned(
+                    variant,
+                    "polymorphic variants cannot have named fields",
+                ));
+            }
+        }
+    }
+    Ok(polymorphic_variant)
+}
+
+/// Returns whether a struct should use the unboxed newtype representation, this
+/// checks that the struct has a single unnamed field.
+pub fn struct_is_unboxed(item: &syn::ItemStruct) -> Result<bool> {
+    if !item.attrs.iter().any(attr_is_ocaml_unboxed) {
+        return Ok(false);
+    }
+    match &item.fields {
+        syn::Fields::Unnamed(_) if !item.generics.params.is_empty() => {
+            Err(Error::new_spanned(item, "ocaml_unboxed is not supported on generic structs"))
+        }
+        syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => Ok(true),
+        _ => Err(Error::new_spanned(item, "ocaml_unboxed is only supported on newtype structs")),
+    }
+}
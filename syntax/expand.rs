@@ -1,16 +1,215 @@
-use super::api::{attr_is_ocaml_deriving, Api, ApiItem, ModItem};
+use super::api::{
+    attr_is_ocaml, attr_is_ocaml_deriving, attr_is_ocaml_unboxed, borrowed_arg,
+    enum_is_polymorphic_variant, struct_is_unboxed, Api, ApiItem, ErrorMode, Lang, ModItem, Type,
+    Unboxed,
+};
 use quote::{quote, ToTokens};
 use std::collections::BTreeSet;
 use syn::parse::Error;
 use syn::spanned::Spanned;
 
 fn expand_enum(item: &syn::ItemEnum, expanded: &mut proc_macro2::TokenStream) -> syn::Result<()> {
-    let mut item = item.clone();
-    item.attrs = item.attrs.into_iter().filter(|x| !attr_is_ocaml_deriving(x)).collect();
-    expanded.extend((&item).into_token_stream());
+    let mut stripped = item.clone();
+    stripped.attrs.retain(|x| !attr_is_ocaml_deriving(x) && !attr_is_ocaml(x));
+    expanded.extend((&stripped).into_token_stream());
+    enum_from_value(item, expanded)?;
+    enum_to_value(item, expanded)
+}
+
+// The hash of a polymorphic variant constructor, as computed by the OCaml
+// compiler for `Name.
+fn hash_variant(variant_ident: &syn::Ident) -> proc_macro2::TokenStream {
+    let name =
+        syn::LitByteStr::new(format!("{}\0", variant_ident).as_bytes(), variant_ident.span());
+    quote! { ocaml_sys::caml_hash_variant(#name.as_ptr()) }
+}
+
+// Polymorphic variants without arguments are represented by the hash of the
+// constructor name, the ones with arguments by a block with tag 0 containing
+// the hash and the argument. Multiple arguments are passed as a tuple.
+fn polymorphic_variant_from_value(
+    item: &syn::ItemEnum,
+    expanded: &mut proc_macro2::TokenStream,
+) -> syn::Result<()> {
+    let (impl_generics, ty_generics, where_clause) =
+        impl_header(&item.generics, Some(quote! { ocaml_rust::from_value::FromSysValue }))?;
     let enum_ident = &item.ident;
+    let mut const_branches: Vec<proc_macro2::TokenStream> = Vec::new();
+    let mut nonconst_branches: Vec<proc_macro2::TokenStream> = Vec::new();
+    for variant in item.variants.iter() {
+        let variant_ident = &variant.ident;
+        let hash = hash_variant(variant_ident);
+        match &variant.fields {
+            syn::Fields::Unit => const_branches.push(quote! {
+                if v == #hash {
+                    return Self::#variant_ident;
+                }
+            }),
+            syn::Fields::Named(_) => {}
+            syn::Fields::Unnamed(u) => {
+                let tys: Vec<&syn::Type> = u.unnamed.iter().map(|field| &field.ty).collect();
+                let value = if tys.len() == 1 {
+                    let ty = tys[0];
+                    quote! {
+                        Self::#variant_ident(<#ty as ocaml_rust::from_value::FromSysValue>::from_value(arg))
+                    }
+                } else {
+                    let nfields = tys.len();
+                    let fields: Vec<proc_macro2::TokenStream> = tys
+                        .iter()
+                        .enumerate()
+                        .map(|(field_idx, ty)| {
+                            quote! {
+                                <#ty as ocaml_rust::from_value::FromSysValue>::from_value(
+                                    *ocaml_sys::field(arg, #field_idx))
+                            }
+                        })
+                        .collect();
+                    quote! {
+                        {
+                            ocaml_rust::from_value::check_tuple(arg, #nfields);
+                            Self::#variant_ident(#(#fields,)*)
+                        }
+                    }
+                };
+                nonconst_branches.push(quote! {
+                    if hash == #hash {
+                        return #value;
+                    }
+                })
+            }
+        }
+    }
+    expanded.extend(quote! {
+        impl #impl_generics ocaml_rust::from_value::FromSysValue for #enum_ident #ty_generics #where_clause {
+            unsafe fn from_value(v: ocaml_sys::Value) -> Self {
+                if ocaml_sys::is_long(v) {
+                    #(#const_branches)*
+                    panic!("unexpected polymorphic variant hash {}", ocaml_sys::int_val(v))
+                } else {
+                    ocaml_rust::from_value::check_tag("polymorphic variant", v, 0);
+                    let hash = *ocaml_sys::field(v, 0);
+                    let arg = *ocaml_sys::field(v, 1);
+                    #(#nonconst_branches)*
+                    panic!("unexpected polymorphic variant hash {}", ocaml_sys::int_val(hash))
+                }
+            }
+        }
+    });
+    Ok(())
+}
 
-    // FromValue
+fn polymorphic_variant_to_value(
+    item: &syn::ItemEnum,
+    expanded: &mut proc_macro2::TokenStream,
+) -> syn::Result<()> {
+    let (impl_generics, ty_generics, where_clause) =
+        impl_header(&item.generics, Some(quote! { ocaml_rust::to_value::ToValue }))?;
+    let (not_f64_generics, _, not_f64_where_clause) = impl_header(&item.generics, None)?;
+    let enum_ident = &item.ident;
+    let mut variants: Vec<proc_macro2::TokenStream> = Vec::new();
+    for variant in item.variants.iter() {
+        let variant_ident = &variant.ident;
+        let hash = hash_variant(variant_ident);
+        let branch = match &variant.fields {
+            syn::Fields::Unit => quote! {
+                Self::#variant_ident => unsafe { #hash }
+            },
+            syn::Fields::Named(_) => continue,
+            syn::Fields::Unnamed(u) => {
+                let nfields = u.unnamed.len();
+                let mut fields: Vec<proc_macro2::TokenStream> = Vec::new();
+                let mut set_fields: Vec<proc_macro2::TokenStream> = Vec::new();
+                for (field_idx, field) in u.unnamed.iter().enumerate() {
+                    let tmp_ident = syn::Ident::new(&format!("_tmp{}", field_idx), u.span());
+                    let field_ident = syn::Ident::new(&format!("_field{}", field_idx), u.span());
+                    let ty = &field.ty;
+                    fields.push(quote! { #field_ident });
+                    set_fields.push(quote! {
+                        let #tmp_ident = <#ty as ocaml_rust::to_value::ToValue>::to_value(#field_ident);
+                        unsafe { ocaml_sys::store_field(rarg.value().value, #field_idx, #tmp_ident)};
+                    })
+                }
+                let arg = if nfields == 1 {
+                    let ty = &u.unnamed[0].ty;
+                    quote! { <#ty as ocaml_rust::to_value::ToValue>::to_value(_field0) }
+                } else {
+                    quote! {
+                        {
+                            let arg = unsafe { ocaml_sys::caml_alloc_tuple(#nfields) };
+                            let rarg : ocaml_rust::RootedValue<()> = ocaml_rust::RootedValue::create(arg);
+                            #(#set_fields)*
+                            rarg.value().value
+                        }
+                    }
+                };
+                quote! {
+                    Self::#variant_ident(#(#fields,)*) => {
+                        let v = unsafe { ocaml_sys::caml_alloc(2, 0) };
+                        let rv : ocaml_rust::RootedValue<()> = ocaml_rust::RootedValue::create(v);
+                        unsafe { ocaml_sys::store_field(rv.value().value, 0, #hash)};
+                        let arg = #arg;
+                        unsafe { ocaml_sys::store_field(rv.value().value, 1, arg)};
+                        rv.value().value
+                    }
+                }
+            }
+        };
+        variants.push(branch);
+    }
+    expanded.extend(quote! {
+        impl #not_f64_generics ocaml_rust::from_value::NotF64 for #enum_ident #ty_generics #not_f64_where_clause {}
+        impl #impl_generics ocaml_rust::to_value::ToValue for #enum_ident #ty_generics #where_clause {
+            fn to_value(&self) -> ocaml_sys::Value
+            {
+                match self {
+                    #(#variants),*
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+// The parts of an impl header for a possibly generic type, every type
+// parameter gets bounded by the implemented trait.
+fn impl_header(
+    generics: &syn::Generics,
+    bound: Option<proc_macro2::TokenStream>,
+) -> syn::Result<(proc_macro2::TokenStream, proc_macro2::TokenStream, proc_macro2::TokenStream)> {
+    if let Some(param) = generics.lifetimes().next() {
+        return Err(Error::new_spanned(param, "lifetime parameters are not supported"));
+    }
+    if let Some(param) = generics.const_params().next() {
+        return Err(Error::new_spanned(param, "const parameters are not supported"));
+    }
+    let mut bounded = generics.clone();
+    if let Some(bound) = bound {
+        for param in bounded.type_params_mut() {
+            param.bounds.push(syn::parse2(bound.clone())?);
+        }
+    }
+    let (impl_generics, _, where_clause) = bounded.split_for_impl();
+    let (_, ty_generics, _) = generics.split_for_impl();
+    Ok((
+        impl_generics.into_token_stream(),
+        ty_generics.into_token_stream(),
+        where_clause.into_token_stream(),
+    ))
+}
+
+/// The FromSysValue implementation for an enum, the OCaml representation uses
+/// the declaration order for the tags of constant and non-constant constructors.
+pub fn enum_from_value(
+    item: &syn::ItemEnum,
+    expanded: &mut proc_macro2::TokenStream,
+) -> syn::Result<()> {
+    if enum_is_polymorphic_variant(item)? {
+        return polymorphic_variant_from_value(item, expanded);
+    }
+    let (impl_generics, ty_generics, where_clause) =
+        impl_header(&item.generics, Some(quote! { ocaml_rust::from_value::FromSysValue }))?;
+    let enum_ident = &item.ident;
     {
         let mut const_branches: Vec<proc_macro2::TokenStream> = Vec::new();
         let mut nonconst_branches: Vec<proc_macro2::TokenStream> = Vec::new();
@@ -78,8 +277,7 @@ fn expand_enum(item: &syn::ItemEnum, expanded: &mut proc_macro2::TokenStream) ->
             }
         }
         expanded.extend(quote! {
-            impl ocaml_rust::from_value::NotF64 for #enum_ident {}
-            impl ocaml_rust::from_value::FromSysValue for #enum_ident {
+            impl #impl_generics ocaml_rust::from_value::FromSysValue for #enum_ident #ty_generics #where_clause {
                 unsafe fn from_value(v: ocaml_sys::Value) -> Self {
                     if ocaml_sys::is_long(v) {
                         match ocaml_sys::int_val(v) {
@@ -96,8 +294,21 @@ fn expand_enum(item: &syn::ItemEnum, expanded: &mut proc_macro2::TokenStream) ->
             }
         });
     }
+    Ok(())
+}
 
-    // ToValue
+/// The ToValue implementation for an enum, this also marks the type as NotF64.
+pub fn enum_to_value(
+    item: &syn::ItemEnum,
+    expanded: &mut proc_macro2::TokenStream,
+) -> syn::Result<()> {
+    if enum_is_polymorphic_variant(item)? {
+        return polymorphic_variant_to_value(item, expanded);
+    }
+    let (impl_generics, ty_generics, where_clause) =
+        impl_header(&item.generics, Some(quote! { ocaml_rust::to_value::ToValue }))?;
+    let (not_f64_generics, _, not_f64_where_clause) = impl_header(&item.generics, None)?;
+    let enum_ident = &item.ident;
     {
         let mut variants: Vec<proc_macro2::TokenStream> = Vec::new();
         let mut const_index = 0isize;
@@ -169,7 +380,8 @@ fn expand_enum(item: &syn::ItemEnum, expanded: &mut proc_macro2::TokenStream) ->
         }
 
         expanded.extend(quote! {
-            impl ocaml_rust::to_value::ToValue for #enum_ident {
+            impl #not_f64_generics ocaml_rust::from_value::NotF64 for #enum_ident #ty_generics #not_f64_where_clause {}
+            impl #impl_generics ocaml_rust::to_value::ToValue for #enum_ident #ty_generics #where_clause {
                 fn to_value(&self) -> ocaml_sys::Value
                 {
                     match self {
@@ -186,25 +398,135 @@ fn expand_struct(
     item: &syn::ItemStruct,
     expanded: &mut proc_macro2::TokenStream,
 ) -> syn::Result<()> {
-    let mut item = item.clone();
-    item.attrs = item.attrs.into_iter().filter(|x| !attr_is_ocaml_deriving(x)).collect();
-    expanded.extend((&item).into_token_stream());
-    let struct_ident = &item.ident;
-    let nfields = item.fields.len();
-    let all_float = item.fields.iter().all(|field| match &field.ty {
-        syn::Type::Path(path) => {
-            let ty = &path.path.segments.last().unwrap().ident;
-            ty == "f32" || ty == "f64"
+    let mut stripped = item.clone();
+    stripped.attrs.retain(|x| !attr_is_ocaml_deriving(x) && !attr_is_ocaml_unboxed(x));
+    expanded.extend((&stripped).into_token_stream());
+    if struct_is_float_record(item) {
+        let struct_ident = &item.ident;
+        let fields = item.fields.len();
+        expanded.extend(quote! {
+            unsafe impl ocaml_rust::borrowed::FloatRecord for #struct_ident {
+                const FIELDS: usize = #fields;
+            }
+        });
+    }
+    struct_from_value(item, expanded)?;
+    struct_to_value(item, expanded)
+}
+
+// `#[repr(C)]` records of `f64` have the layout of the OCaml flat float record so
+// they can be borrowed in place.
+fn struct_is_float_record(item: &syn::ItemStruct) -> bool {
+    let repr_c = item.attrs.iter().any(|attr| {
+        attr.path.is_ident("repr")
+            && matches!(attr.parse_meta(), Ok(syn::Meta::List(list))
+                if list.nested.iter().any(|nested| matches!(nested,
+                    syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("C"))))
+    });
+    // A single field #[ocaml_unboxed] struct is represented as a float, not as
+    // a float record.
+    repr_c
+        && item.generics.params.is_empty()
+        && !struct_is_unboxed(item).unwrap_or(true)
+        && struct_is_all_float(item)
+        && item.fields.iter().all(|field| match &field.ty {
+            syn::Type::Path(path) => path.path.is_ident("f64"),
+            _ => false,
+        })
+}
+
+// Immutable references to float records defined in the same bridge are borrowed.
+fn float_record_arg(ty: &syn::Type, float_records: &BTreeSet<String>) -> bool {
+    match ty {
+        syn::Type::Reference(reference) if reference.mutability.is_none() => {
+            match reference.elem.as_ref() {
+                syn::Type::Path(path) => {
+                    path.qself.is_none()
+                        && path
+                            .path
+                            .get_ident()
+                            .is_some_and(|ident| float_records.contains(&ident.to_string()))
+                }
+                _ => false,
+            }
         }
         _ => false,
-    });
+    }
+}
+
+// Records where all the fields are floats use the flat float representation,
+// this does not apply to tuple structs as constructor arguments are not flattened.
+fn struct_is_all_float(item: &syn::ItemStruct) -> bool {
+    let named = matches!(item.fields, syn::Fields::Named(_));
+    named
+        && !item.fields.is_empty()
+        && item.fields.iter().all(|field| match &field.ty {
+            syn::Type::Path(path) => {
+                let ty = &path.path.segments.last().unwrap().ident;
+                ty == "f32" || ty == "f64"
+            }
+            _ => false,
+        })
+}
+
+// The variables bound to each field when destructuring or building the struct.
+fn struct_field_idents(item: &syn::ItemStruct) -> Vec<syn::Ident> {
+    item.fields
+        .iter()
+        .enumerate()
+        .map(|(field_idx, field)| match &field.ident {
+            Some(ident) => ident.clone(),
+            None => syn::Ident::new(&format!("_field{}", field_idx), item.span()),
+        })
+        .collect()
+}
+
+// A pattern or expression for the struct with the fields bound to the given idents.
+fn struct_pattern(item: &syn::ItemStruct, idents: &[syn::Ident]) -> proc_macro2::TokenStream {
+    let struct_ident = &item.ident;
+    match &item.fields {
+        syn::Fields::Named(_) => quote! { #struct_ident { #(#idents,)* } },
+        syn::Fields::Unnamed(_) => quote! { #struct_ident ( #(#idents,)* ) },
+        syn::Fields::Unit => quote! { #struct_ident },
+    }
+}
+
+/// The FromSysValue implementation for a struct.
+pub fn struct_from_value(
+    item: &syn::ItemStruct,
+    expanded: &mut proc_macro2::TokenStream,
+) -> syn::Result<()> {
+    let (impl_generics, ty_generics, where_clause) =
+        impl_header(&item.generics, Some(quote! { ocaml_rust::from_value::FromSysValue }))?;
+    let struct_ident = &item.ident;
+    let all_float = struct_is_all_float(item);
+    let field_idents = struct_field_idents(item);
+    let pattern = struct_pattern(item, &field_idents);
+    if struct_is_unboxed(item)? {
+        let ty = &item.fields.iter().next().unwrap().ty;
+        expanded.extend(quote! {
+            impl #impl_generics ocaml_rust::from_value::FromSysValue for #struct_ident #ty_generics #where_clause {
+                unsafe fn from_value(v: ocaml_sys::Value) -> Self {
+                    #struct_ident(<#ty as ocaml_rust::from_value::FromSysValue>::from_value(v))
+                }
+            }
+        });
+        return Ok(());
+    }
+    if item.fields.is_empty() {
+        expanded.extend(quote! {
+            impl #impl_generics ocaml_rust::from_value::FromSysValue for #struct_ident #ty_generics #where_clause {
+                unsafe fn from_value(_v: ocaml_sys::Value) -> Self {
+                    #pattern
+                }
+            }
+        });
+        return Ok(());
+    }
     {
-        let mut fields: Vec<proc_macro2::TokenStream> = Vec::new();
         let mut let_fields: Vec<proc_macro2::TokenStream> = Vec::new();
-        for (field_idx, field) in item.fields.iter().enumerate() {
-            let field_ident = &field.ident;
+        for (field_idx, (field, field_ident)) in item.fields.iter().zip(&field_idents).enumerate() {
             let ty = &field.ty;
-            fields.push(quote! { #field_ident });
             let_fields.push(quote! {
                 let _tmp_value = ocaml_sys::field(v, #field_idx);
                 let #field_ident =
@@ -214,8 +536,9 @@ fn expand_struct(
 
         if all_float {
             let mut let_fields_float: Vec<proc_macro2::TokenStream> = Vec::new();
-            for (field_idx, field) in item.fields.iter().enumerate() {
-                let field_ident = &field.ident;
+            for (field_idx, (field, field_ident)) in
+                item.fields.iter().zip(&field_idents).enumerate()
+            {
                 let ty = &field.ty;
                 let_fields_float.push(quote! {
                     let _tmp_value = ocaml_sys::field(v, #field_idx);
@@ -224,40 +547,85 @@ fn expand_struct(
             }
 
             expanded.extend(quote! {
-                impl ocaml_rust::from_value::FromSysValue for #struct_ident {
+                impl #impl_generics ocaml_rust::from_value::FromSysValue for #struct_ident #ty_generics #where_clause {
                     unsafe fn from_value(v: ocaml_sys::Value) -> Self {
                         let tag = ocaml_sys::tag_val(v);
                         if tag == ocaml_sys::DOUBLE_ARRAY {
                             #(#let_fields_float)*
-                            #struct_ident { #(#fields,)* }
+                            #pattern
                         } else {
                             ocaml_rust::from_value::check_tag("record", v, 0);
                             #(#let_fields)*
-                            #struct_ident { #(#fields,)* }
+                            #pattern
                         }
                     }
                 }
             });
         } else {
             expanded.extend(quote! {
-                impl ocaml_rust::from_value::FromSysValue for #struct_ident {
+                impl #impl_generics ocaml_rust::from_value::FromSysValue for #struct_ident #ty_generics #where_clause {
                     unsafe fn from_value(v: ocaml_sys::Value) -> Self {
                             ocaml_rust::from_value::check_tag("record", v, 0);
                             #(#let_fields)*
-                            #struct_ident { #(#fields,)* }
+                            #pattern
                         }
                     }
             });
         }
     }
+    Ok(())
+}
+
+/// The ToValue implementation for a struct, this also marks the type as NotF64.
+pub fn struct_to_value(
+    item: &syn::ItemStruct,
+    expanded: &mut proc_macro2::TokenStream,
+) -> syn::Result<()> {
+    let (impl_generics, ty_generics, where_clause) =
+        impl_header(&item.generics, Some(quote! { ocaml_rust::to_value::ToValue }))?;
+    let (not_f64_generics, _, not_f64_where_clause) = impl_header(&item.generics, None)?;
+    let struct_ident = &item.ident;
+    let nfields = item.fields.len();
+    let all_float = struct_is_all_float(item);
+    let field_idents = struct_field_idents(item);
+    let pattern = struct_pattern(item, &field_idents);
+    if struct_is_unboxed(item)? {
+        let ty = &item.fields.iter().next().unwrap().ty;
+        // An unboxed newtype around a float has the float representation.
+        let not_f64 = match ty {
+            syn::Type::Path(path) if path.path.is_ident("f32") || path.path.is_ident("f64") => {
+                quote! {}
+            }
+            _ => {
+                quote! { impl #not_f64_generics ocaml_rust::from_value::NotF64 for #struct_ident #ty_generics #not_f64_where_clause {} }
+            }
+        };
+        expanded.extend(quote! {
+            #not_f64
+            impl #impl_generics ocaml_rust::to_value::ToValue for #struct_ident #ty_generics #where_clause {
+                fn to_value(&self) -> ocaml_sys::Value {
+                    <#ty as ocaml_rust::to_value::ToValue>::to_value(&self.0)
+                }
+            }
+        });
+        return Ok(());
+    }
+    if item.fields.is_empty() {
+        expanded.extend(quote! {
+            impl #not_f64_generics ocaml_rust::from_value::NotF64 for #struct_ident #ty_generics #not_f64_where_clause {}
+            impl #impl_generics ocaml_rust::to_value::ToValue for #struct_ident #ty_generics #where_clause {
+                fn to_value(&self) -> ocaml_sys::Value {
+                    ocaml_sys::UNIT
+                }
+            }
+        });
+        return Ok(());
+    }
     {
-        let mut fields: Vec<proc_macro2::TokenStream> = Vec::new();
         let mut set_fields: Vec<proc_macro2::TokenStream> = Vec::new();
-        for (field_idx, field) in item.fields.iter().enumerate() {
+        for (field_idx, (field, field_ident)) in item.fields.iter().zip(&field_idents).enumerate() {
             let tmp_ident = syn::Ident::new(&format!("_tmp{}", field_idx), item.span());
-            let field_ident = &field.ident;
             let ty = &field.ty;
-            fields.push(quote! { #field_ident });
             let q = if all_float {
                 quote! {
                     let #tmp_ident = unsafe { ocaml_sys::field(v, #field_idx) as *mut f64 };
@@ -279,11 +647,11 @@ fn expand_struct(
         };
 
         expanded.extend(quote! {
-            impl ocaml_rust::from_value::NotF64 for #struct_ident {}
-            impl ocaml_rust::to_value::ToValue for #struct_ident {
+            impl #not_f64_generics ocaml_rust::from_value::NotF64 for #struct_ident #ty_generics #not_f64_where_clause {}
+            impl #impl_generics ocaml_rust::to_value::ToValue for #struct_ident #ty_generics #where_clause {
                 fn to_value(&self) -> ocaml_sys::Value
                 {
-                    let #struct_ident { #(#fields,)* } = self;
+                    let #pattern = self;
                     let v = unsafe { #init };
                     let rv : ocaml_rust::RootedValue<()> = ocaml_rust::RootedValue::create(v);
                     #(#set_fields)*
@@ -304,6 +672,339 @@ fn is_ref(ty: &syn::Type) -> bool {
     matches!(ty, syn::Type::Reference(_))
 }
 
+fn expand_rust_fn(
+    api_ident: &proc_macro2::Ident,
+    item: &ModItem,
+    float_records: &BTreeSet<String>,
+    expanded: &mut proc_macro2::TokenStream,
+) -> syn::Result<()> {
+    match item {
+        ModItem::Fn { ident, args, output: (output, output_type), attrs } => {
+            let borrowed_arg = |ty: &syn::Type| {
+                borrowed_arg(ty).or_else(|| float_record_arg(ty, float_records).then_some(false))
+            };
+            if attrs.release_runtime_lock
+                && args.iter().any(|(_, ty, _)| float_record_arg(ty, float_records))
+            {
+                return Err(Error::new(
+                    ident.span(),
+                    "borrowed float records cannot be used with release_runtime_lock",
+                ));
+            }
+            let ocaml_ident = syn::Ident::new(
+                &c_fn_name(api_ident, ident, attrs.namespace.as_ref()),
+                ident.span(),
+            );
+            let arg_with_types: Vec<_> =
+                args.iter().map(|(ident, _ty, _ty2)| quote! { #ident: ocaml_sys::Value}).collect();
+            let args_conv: Vec<_> = args
+                .iter()
+                .filter(|(_ident, ty, _typ)| borrowed_arg(ty).is_none())
+                .map(|(ident, ty, _typ)| {
+                    let ty = match ty.as_ref() {
+                        syn::Type::Reference(ty) => ty.elem.as_ref(),
+                        other => other,
+                    };
+                    quote! {
+                    let mut #ident = unsafe {
+                        <#ty as ocaml_rust::from_value::FromSysValue>::from_value(#ident) };
+                    }
+                })
+                .collect();
+            // Borrowed slices are created last as converting the other arguments
+            // may allocate. A mutable borrow cannot alias another borrowed argument.
+            let borrowed: Vec<_> = args
+                .iter()
+                .filter_map(|(ident, ty, _typ)| {
+                    borrowed_arg(ty).map(|mutable| (ident, ty, mutable))
+                })
+                .collect();
+            let mut borrows_conv: Vec<_> = vec![];
+            // Bigarray content is outside of the OCaml heap, sub-arrays share it so
+            // the check is on overlapping address ranges. Bigarrays passed by value
+            // give access to data_mut so they count as mutable.
+            let bigarrays: Vec<_> = args
+                .iter()
+                .filter(|(_ident, _ty, typ)| matches!(typ, Type::BigArray(..)))
+                .map(|(ident, ty, _typ)| {
+                    let mutable = match ty.as_ref() {
+                        syn::Type::Reference(r) => r.mutability.is_some(),
+                        _ => true,
+                    };
+                    (ident, mutable)
+                })
+                .collect();
+            for (index, (ident, mutable)) in bigarrays.iter().enumerate() {
+                for (other, other_mutable) in bigarrays.iter().skip(index + 1) {
+                    if *mutable || *other_mutable {
+                        let message =
+                            format!("{} and {} must not overlap", ident.ident, other.ident);
+                        borrows_conv.push(quote! {
+                            if ocaml_rust::bigarray::overlap(#ident.data_range(), #other.data_range()) {
+                                ocaml_rust::panic::invalid_argument(#message.to_string())
+                            }
+                        });
+                    }
+                }
+            }
+            for (index, (ident, _ty, mutable)) in borrowed.iter().enumerate() {
+                for (other, _ty, other_mutable) in borrowed.iter().skip(index + 1) {
+                    if *mutable || *other_mutable {
+                        let message = format!("{} and {} must not alias", ident.ident, other.ident);
+                        borrows_conv.push(quote! {
+                            if #ident == #other {
+                                ocaml_rust::panic::invalid_argument(#message.to_string())
+                            }
+                        });
+                    }
+                }
+            }
+            for (ident, ty, mutable) in borrowed.iter() {
+                let elem = match ty.as_ref() {
+                    syn::Type::Reference(ty) => ty.elem.as_ref(),
+                    other => other,
+                };
+                borrows_conv.push(if *mutable {
+                    quote! {
+                        let #ident: &mut #elem = unsafe {
+                            <#elem as ocaml_rust::borrowed::BorrowMutFromValue>::borrow_mut_from_value(#ident) };
+                    }
+                } else {
+                    quote! {
+                        let #ident: &#elem = unsafe {
+                            <#elem as ocaml_rust::borrowed::BorrowFromValue>::borrow_from_value(#ident) };
+                    }
+                });
+            }
+            // Calling back into OCaml while the borrows are live panics.
+            let (borrow_guard, borrow_guard_end) = if borrowed.is_empty() {
+                (quote! {}, quote! {})
+            } else {
+                (
+                    quote! { let borrow_guard = ocaml_rust::borrowed::BorrowGuard::enter(); },
+                    quote! { drop(borrow_guard); },
+                )
+            };
+            let (strict_utf8, strict_utf8_end) = if attrs.strict_utf8 {
+                (
+                    quote! { let strict_utf8 = ocaml_rust::from_value::StrictUtf8::enable(); },
+                    quote! { drop(strict_utf8); },
+                )
+            } else {
+                (quote! {}, quote! {})
+            };
+            let arg_idents: Vec<_> = args.iter().map(|(ident, _ty, _typ)| ident).collect();
+            let args: Vec<_> = args
+                .iter()
+                .map(|(ident, ty, _typ)| {
+                    if borrowed_arg(ty).is_some() {
+                        quote! { #ident }
+                    } else if is_ref(ty.as_ref()) {
+                        quote! { &mut #ident }
+                    } else {
+                        quote! { #ident }
+                    }
+                })
+                .collect();
+            let namespace_ident = match &attrs.namespace {
+                None => quote! { #ident},
+                Some(namespace) => {
+                    let namespace = namespace.iter().map(|s| syn::Ident::new(s, ident.span()));
+                    quote! { #(#namespace)::*::#ident }
+                }
+            };
+            let (maybe_release_runtime_lock, maybe_acquire_runtime_lock) =
+                if attrs.release_runtime_lock {
+                    (
+                        quote! { let release_lock = ocaml_rust::RuntimeLock::release(); },
+                        quote! { drop(release_lock); },
+                    )
+                } else {
+                    (quote! {}, quote! {})
+                };
+            // When raise is set, the conversion returns a result and the error case
+            // is raised outside of the closure so that all the rust values have been
+            // dropped at this point.
+            let (to_value, raise) = match (output_type, attrs.rust_error) {
+                (Type::OCamlResult(_), _) => (
+                    quote! { ocaml_rust::exn::to_value_or_exn(&res) },
+                    Some(quote! { ocaml_rust::exn::raise }),
+                ),
+                (_, ErrorMode::String) => {
+                    (quote! { <#output as ocaml_rust::to_value::ToValue>::to_value(&res) }, None)
+                }
+                (_, ErrorMode::Record) => {
+                    (quote! { ocaml_rust::error::to_value_with_details(&res) }, None)
+                }
+                (_, ErrorMode::Exception) => (
+                    quote! { ocaml_rust::error::to_value_or_details(&res) },
+                    Some(quote! { ocaml_rust::error::raise_rust_error }),
+                ),
+            };
+            let body = quote! {
+                ocaml_rust::panic::catch_panic(move || {
+                    #strict_utf8
+                    #(#args_conv)*;
+                    #strict_utf8_end
+                    #(#borrows_conv)*
+                    #borrow_guard
+                    #[allow(clippy::unnecessary_mut_passed)]
+                    #maybe_release_runtime_lock
+                    let mut res: #output = #namespace_ident(#(#args),*);
+                    #maybe_acquire_runtime_lock
+                    #borrow_guard_end
+                    #to_value
+                })
+            };
+            let body = match raise {
+                None => body,
+                Some(raise) => quote! {
+                    match #body {
+                        Ok(v) => v,
+                        Err(err) => #raise(err),
+                    }
+                },
+            };
+            expanded.extend(quote! {
+            #[no_mangle]
+            pub extern "C" fn #ocaml_ident(#(#arg_with_types),*) -> ocaml_sys::Value {
+                #body
+            } });
+            if needs_bytecode_stub(arg_idents.len()) {
+                let bytecode_ident = syn::Ident::new(
+                    &bytecode_fn_name(api_ident, ident, attrs.namespace.as_ref()),
+                    ident.span(),
+                );
+                let arg_indexes = 0..arg_idents.len();
+                expanded.extend(quote! {
+                #[no_mangle]
+                pub unsafe extern "C" fn #bytecode_ident(
+                    argv: *const ocaml_sys::Value,
+                    argc: std::os::raw::c_int,
+                ) -> ocaml_sys::Value {
+                    let argv = unsafe { std::slice::from_raw_parts(argv, argc as usize) };
+                    #(let #arg_idents = argv[#arg_indexes];)*
+                    #ocaml_ident(#(#arg_idents),*)
+                } })
+            }
+            if let Some((unboxed_args, unboxed_output)) = item.unboxed_signature() {
+                let unboxed_ident = syn::Ident::new(
+                    &unboxed_fn_name(api_ident, ident, attrs.namespace.as_ref()),
+                    ident.span(),
+                );
+                let c_type = |unboxed: &Unboxed| match unboxed {
+                    Unboxed::Float => quote! { f64 },
+                    Unboxed::Int64 => quote! { i64 },
+                    Unboxed::Int32 => quote! { i32 },
+                    Unboxed::Int => quote! { isize },
+                    Unboxed::Bool | Unboxed::Unit => quote! { ocaml_sys::Value },
+                };
+                let arg_with_types: Vec<_> = arg_idents
+                    .iter()
+                    .zip(unboxed_args.iter())
+                    .map(|(ident, unboxed)| {
+                        let c_type = c_type(unboxed);
+                        quote! { #ident: #c_type }
+                    })
+                    .collect();
+                let args_conv: Vec<_> = arg_idents
+                    .iter()
+                    .zip(unboxed_args.iter())
+                    .map(|(ident, unboxed)| match unboxed {
+                        Unboxed::Bool => {
+                            quote! { let mut #ident = unsafe { ocaml_sys::int_val(#ident) != 0 }; }
+                        }
+                        Unboxed::Float
+                        | Unboxed::Int64
+                        | Unboxed::Int32
+                        | Unboxed::Int
+                        | Unboxed::Unit => quote! { let mut #ident = #ident; },
+                    })
+                    .collect();
+                let c_output = c_type(&unboxed_output);
+                let output_conv = match unboxed_output {
+                    Unboxed::Bool => quote! { unsafe { ocaml_sys::val_int(res as isize) } },
+                    Unboxed::Unit => quote! { ocaml_sys::UNIT },
+                    Unboxed::Float | Unboxed::Int64 | Unboxed::Int32 | Unboxed::Int => {
+                        quote! { res }
+                    }
+                };
+                let body = quote! {
+                    #(#args_conv)*
+                    #[allow(clippy::unnecessary_mut_passed)]
+                    #maybe_release_runtime_lock
+                    let res: #output = #namespace_ident(#(#args),*);
+                    #maybe_acquire_runtime_lock
+                    #output_conv
+                };
+                // Raising from a noalloc external is not allowed so panics are not
+                // caught in this case and result in an abort.
+                let body = if attrs.noalloc {
+                    body
+                } else {
+                    quote! { ocaml_rust::panic::catch_panic(move || { #body }) }
+                };
+                expanded.extend(quote! {
+                #[no_mangle]
+                pub extern "C" fn #unboxed_ident(#(#arg_with_types),*) -> #c_output {
+                    #body
+                } })
+            }
+        }
+    }
+    Ok(())
+}
+
+// Functions from an extern "OCaml" block are looked up by name so the OCaml
+// side has to register them via Callback.register.
+fn expand_ocaml_fn(
+    api_ident: &proc_macro2::Ident,
+    item: &ModItem,
+    expanded: &mut proc_macro2::TokenStream,
+) -> syn::Result<()> {
+    match item {
+        ModItem::Fn { ident, args, output: (output, _), attrs } => {
+            let name = c_fn_name(api_ident, ident, attrs.namespace.as_ref());
+            let arg_with_types: Vec<_> =
+                args.iter().map(|(ident, ty, _typ)| quote! { #ident: #ty }).collect();
+            let args_conv: Vec<_> = args
+                .iter()
+                .map(|(ident, ty, _typ)| {
+                    let (ty, arg) = match ty.as_ref() {
+                        syn::Type::Reference(ty) => (ty.elem.as_ref(), quote! { #ident }),
+                        other => (other, quote! { &#ident }),
+                    };
+                    quote! {
+                        let #ident: ocaml_rust::RootedValue<()> = ocaml_rust::RootedValue::create(
+                            <#ty as ocaml_rust::to_value::ToValue>::to_value(#arg));
+                    }
+                })
+                .collect();
+            let args: Vec<_> = if args.is_empty() {
+                vec![quote! { ocaml_sys::UNIT }]
+            } else {
+                args.iter().map(|(ident, _ty, _typ)| quote! { #ident.value().value }).collect()
+            };
+            let docs = &attrs.docs;
+            let deprecated = match attrs.deprecated.as_deref() {
+                None => quote! {},
+                Some("") => quote! { #[deprecated] },
+                Some(note) => quote! { #[deprecated(note = #note)] },
+            };
+            expanded.extend(quote! {
+                #(#[doc = #docs])*
+                #deprecated
+                pub fn #ident<'a>(#(#arg_with_types),*) -> ocaml_rust::exn::Result<'a, #output> {
+                    ocaml_rust::borrowed::check_no_borrow("call an OCaml function");
+                    #(#args_conv)*
+                    ocaml_rust::closure::call_named(#name, &mut [#(#args),*])
+                }
+            })
+        }
+    }
+    Ok(())
+}
+
 #[allow(dead_code)]
 pub fn c_fn_name(
     api_ident: &proc_macro2::Ident,
@@ -314,75 +1015,239 @@ pub fn c_fn_name(
     format!("__ocaml_{}{}_{}", api_ident, namespace, ident)
 }
 
+// The bytecode runtime passes the arguments of externals with more than five
+// arguments as an array, these require a separate stub.
+// https://ocaml.org/manual/intfc.html#ss:c-prim-impl
+const MAX_BYTECODE_DIRECT_ARGS: usize = 5;
+
+#[allow(dead_code)]
+pub fn needs_bytecode_stub(nargs: usize) -> bool {
+    nargs > MAX_BYTECODE_DIRECT_ARGS
+}
+
+#[allow(dead_code)]
+pub fn bytecode_fn_name(
+    api_ident: &proc_macro2::Ident,
+    ident: &proc_macro2::Ident,
+    namespace: Option<&Vec<String>>,
+) -> String {
+    format!("{}_bytecode", c_fn_name(api_ident, ident, namespace))
+}
+
+#[allow(dead_code)]
+pub fn unboxed_fn_name(
+    api_ident: &proc_macro2::Ident,
+    ident: &proc_macro2::Ident,
+    namespace: Option<&Vec<String>>,
+) -> String {
+    format!("{}_unboxed", c_fn_name(api_ident, ident, namespace))
+}
+
+/// The stub returning the layout fingerprint of the types defined in a bridge.
+#[allow(dead_code)]
+pub fn layout_fn_name(api_ident: &proc_macro2::Ident) -> String {
+    format!("__ocaml_{}__layout_fingerprint", api_ident)
+}
+
+// Appends the tokens separated by spaces, this avoids depending on the
+// formatting of TokenStream which differs between the compiler and the
+// fallback implementation used by the generator.
+fn canonical_tokens(tokens: proc_macro2::TokenStream, out: &mut String) {
+    for token in tokens {
+        match token {
+            proc_macro2::TokenTree::Group(group) => {
+                let (open, close) = match group.delimiter() {
+                    proc_macro2::Delimiter::Parenthesis => ("(", ")"),
+                    proc_macro2::Delimiter::Brace => ("{", "}"),
+                    proc_macro2::Delimiter::Bracket => ("[", "]"),
+                    proc_macro2::Delimiter::None => ("", ""),
+                };
+                out.push_str(open);
+                out.push(' ');
+                canonical_tokens(group.stream(), out);
+                out.push_str(close);
+            }
+            proc_macro2::TokenTree::Ident(ident) => out.push_str(&ident.to_string()),
+            proc_macro2::TokenTree::Punct(punct) => out.push(punct.as_char()),
+            proc_macro2::TokenTree::Literal(literal) => out.push_str(&literal.to_string()),
+        }
+        out.push(' ');
+    }
+}
+
+// Only the attributes that change the OCaml representation are part of the
+// layout, docs or derives are not.
+fn layout_attrs(attrs: &mut Vec<syn::Attribute>) {
+    attrs.retain(|attr| attr_is_ocaml(attr) || attr_is_ocaml_unboxed(attr))
+}
+
+// 64 bits FNV-1a, this has to be stable across compilers and platforms.
+fn fnv1a(s: &str) -> u64 {
+    let mut hash = 0xcbf29ce484222325u64;
+    for byte in s.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+// The tokens of a struct or enum that determine its OCaml representation.
+fn item_layout(api_item: &ApiItem) -> Option<proc_macro2::TokenStream> {
+    match api_item {
+        ApiItem::Enum(item) => {
+            let mut item = item.clone();
+            layout_attrs(&mut item.attrs);
+            item.vis = syn::Visibility::Inherited;
+            for variant in item.variants.iter_mut() {
+                layout_attrs(&mut variant.attrs);
+                for field in variant.fields.iter_mut() {
+                    layout_attrs(&mut field.attrs);
+                    field.vis = syn::Visibility::Inherited;
+                }
+            }
+            Some(item.into_token_stream())
+        }
+        ApiItem::Struct(item) => {
+            let mut item = item.clone();
+            layout_attrs(&mut item.attrs);
+            item.vis = syn::Visibility::Inherited;
+            for field in item.fields.iter_mut() {
+                layout_attrs(&mut field.attrs);
+                field.vis = syn::Visibility::Inherited;
+            }
+            Some(item.into_token_stream())
+        }
+        ApiItem::ForeignMod { .. } | ApiItem::Type(_) | ApiItem::Include(_) | ApiItem::Other(_) => {
+            None
+        }
+    }
+}
+
+// The parts of a function signature that determine the OCaml external or the
+// registered callback type, argument names are not included.
+fn signature_layout(lang: &Lang, item: &ModItem) -> proc_macro2::TokenStream {
+    let ModItem::Fn { ident, args, output, attrs } = item;
+    let lang = match lang {
+        Lang::OCaml => quote! { OCaml },
+        Lang::Rust => quote! { Rust },
+    };
+    let tys = args.iter().map(|(_, ty, _)| ty);
+    let output = &output.0;
+    let rust_error = syn::Ident::new(&format!("{:?}", attrs.rust_error), ident.span());
+    let noalloc = attrs.noalloc;
+    quote! { extern #lang fn #ident(#(#tys),*) -> #output; #rust_error #noalloc }
+}
+
+fn fingerprint(layout: &str) -> String {
+    format!("{:016x}", fnv1a(layout))
+}
+
+/// The fingerprint of a struct or enum deriving `ToValue` or `FromValue`
+/// outside of a bridge.
+#[allow(dead_code)]
+pub fn derived_layout_fingerprint(api_item: &ApiItem) -> Option<String> {
+    let mut layout = String::new();
+    canonical_tokens(item_layout(api_item)?, &mut layout);
+    Some(fingerprint(&layout))
+}
+
+/// The stub returning the layout fingerprint of a type deriving `ToValue`, or
+/// `FromValue` when `to_value` is false. Both derives define one so that either
+/// can be used on its own. The crate name is part of the symbol so that types
+/// with the same name in different crates can be linked together.
+#[allow(dead_code)]
+pub fn derived_layout_fn_name(
+    crate_name: &str,
+    ident: &proc_macro2::Ident,
+    to_value: bool,
+) -> String {
+    let derive = if to_value { "to_value" } else { "from_value" };
+    format!("__ocaml_rust_{}_{}__{}__layout_fingerprint", derive, crate_name, ident)
+}
+
+/// Defines the stub returning the layout fingerprint of a derived type.
+#[allow(dead_code)]
+pub fn derived_layout_stub(
+    api_item: &ApiItem,
+    ident: &proc_macro2::Ident,
+    to_value: bool,
+    expanded: &mut proc_macro2::TokenStream,
+) {
+    if let Some(fingerprint) = derived_layout_fingerprint(api_item) {
+        // This is set by cargo when compiling the crate using the derive.
+        let crate_name = std::env::var("CARGO_CRATE_NAME").unwrap_or_default();
+        let fn_name = derived_layout_fn_name(&crate_name, ident, to_value);
+        let layout_ident = syn::Ident::new(&fn_name, ident.span());
+        expanded.extend(quote! {
+            #[no_mangle]
+            pub extern "C" fn #layout_ident(_unit: ocaml_sys::Value) -> ocaml_sys::Value {
+                <String as ocaml_rust::to_value::ToValue>::to_value(&#fingerprint.to_string())
+            }
+        });
+    }
+}
+
 impl Api {
+    /// A fingerprint of the layout of the structs and enums defined in the
+    /// bridge and of the function signatures, the generated OCaml code checks
+    /// it against the one from the Rust stubs when loaded. Returns None when
+    /// the bridge defines no such types or functions.
+    #[allow(dead_code)]
+    pub fn layout_fingerprint(&self) -> Option<String> {
+        let mut layout = String::new();
+        for api_item in self.api_items.iter() {
+            match api_item {
+                ApiItem::ForeignMod { lang, items, .. } => {
+                    for item in items.iter() {
+                        canonical_tokens(signature_layout(lang, item), &mut layout)
+                    }
+                }
+                api_item => {
+                    if let Some(tokens) = item_layout(api_item) {
+                        canonical_tokens(tokens, &mut layout)
+                    }
+                }
+            }
+        }
+        if layout.is_empty() {
+            None
+        } else {
+            Some(fingerprint(&layout))
+        }
+    }
+
     #[allow(dead_code)]
     pub fn expand(&self) -> syn::Result<proc_macro2::TokenStream> {
         let mut expanded = proc_macro2::TokenStream::new();
+        if let Some(fingerprint) = self.layout_fingerprint() {
+            let layout_ident = syn::Ident::new(&layout_fn_name(&self.ident), self.ident.span());
+            expanded.extend(quote! {
+                #[no_mangle]
+                pub extern "C" fn #layout_ident(_unit: ocaml_sys::Value) -> ocaml_sys::Value {
+                    <String as ocaml_rust::to_value::ToValue>::to_value(&#fingerprint.to_string())
+                }
+            });
+        }
+        let float_records: BTreeSet<String> = self
+            .api_items
+            .iter()
+            .filter_map(|api_item| match api_item {
+                ApiItem::Struct(item) if struct_is_float_record(item) => {
+                    Some(item.ident.to_string())
+                }
+                _ => None,
+            })
+            .collect();
         for item in self.api_items.iter() {
             match item {
-                ApiItem::ForeignMod { attrs: _, lang: _, brace_token: _, items } => {
+                ApiItem::ForeignMod { attrs: _, lang: Lang::OCaml, brace_token: _, items } => {
                     for item in items.iter() {
-                        match item {
-                            ModItem::Fn { ident, args, output: (output, _), attrs } => {
-                                let ocaml_ident = syn::Ident::new(
-                                    &c_fn_name(&self.ident, ident, attrs.namespace.as_ref()),
-                                    ident.span(),
-                                );
-                                let arg_with_types: Vec<_> = args
-                                    .iter()
-                                    .map(|(ident, _ty, _ty2)| quote! { #ident: ocaml_sys::Value})
-                                    .collect();
-                                let args_conv: Vec<_> =
-                                    args.iter().map(|(ident, ty, _typ)| {
-                                            let ty = match ty.as_ref() {
-                                                syn::Type::Reference(ty) => ty.elem.as_ref(),
-                                                other => other,
-                                            };
-                                        quote! {
-                                        let mut #ident = unsafe {
-                                            <#ty as ocaml_rust::from_value::FromSysValue>::from_value(#ident) };
-                                        }}).collect();
-                                let args: Vec<_> = args
-                                    .iter()
-                                    .map(|(ident, ty, _typ)| {
-                                        if is_ref(ty.as_ref()) {
-                                            quote! { &mut #ident }
-                                        } else {
-                                            quote! { #ident }
-                                        }
-                                    })
-                                    .collect();
-                                let namespace_ident = match &attrs.namespace {
-                                    None => quote! { #ident},
-                                    Some(namespace) => {
-                                        let namespace = namespace
-                                            .iter()
-                                            .map(|s| syn::Ident::new(s, ident.span()));
-                                        quote! { #(#namespace)::*::#ident }
-                                    }
-                                };
-                                let (maybe_release_runtime_lock, maybe_acquire_runtime_lock) =
-                                    if attrs.release_runtime_lock {
-                                        (
-                                            quote! { let release_lock = ocaml_rust::RuntimeLock::release(); },
-                                            quote! { drop(release_lock); },
-                                        )
-                                    } else {
-                                        (quote! {}, quote! {})
-                                    };
-                                expanded.extend(quote! {
-                                #[no_mangle]
-                                pub extern "C" fn #ocaml_ident(#(#arg_with_types),*) -> ocaml_sys::Value {
-                                    ocaml_rust::initial_setup();
-                                    #(#args_conv)*;
-                                    #[allow(clippy::unnecessary_mut_passed)]
-                                    #maybe_release_runtime_lock
-                                    let mut res: #output = #namespace_ident(#(#args),*);
-                                    #maybe_acquire_runtime_lock
-                                    <#output as ocaml_rust::to_value::ToValue>::to_value(&res)
-                                } })
-                            }
-                        }
+                        expand_ocaml_fn(&self.ident, item, &mut expanded)?
+                    }
+                }
+                ApiItem::ForeignMod { attrs: _, lang: Lang::Rust, brace_token: _, items } => {
+                    for item in items.iter() {
+                        expand_rust_fn(&self.ident, item, &float_records, &mut expanded)?
                     }
                 }
                 ApiItem::Enum(item) => expand_enum(item, &mut expanded)?,
@@ -1,22 +1,25 @@
-use crate::syntax::api::Api;
+use crate::syntax::api::{Api, ApiItem};
 use syn::parse::discouraged::Speculative;
 use syn::parse::{Error, Parse, ParseStream, Result};
 use syn::{braced, Attribute, Ident, Item, Token, Visibility};
 
 pub struct File {
     pub apis: Vec<Api>,
+    /// Structs and enums deriving `ToValue` or `FromValue` outside of a bridge.
+    pub derived: Vec<ApiItem>,
 }
 
 impl Parse for File {
     fn parse(input: ParseStream) -> Result<Self> {
         let mut apis: Vec<Api> = Vec::new();
+        let mut derived: Vec<ApiItem> = Vec::new();
         input.call(Attribute::parse_inner)?;
-        parse(input, &mut apis)?;
-        Ok(File { apis })
+        parse(input, &mut apis, &mut derived)?;
+        Ok(File { apis, derived })
     }
 }
 
-fn parse(input: ParseStream, apis: &mut Vec<Api>) -> Result<()> {
+fn parse(input: ParseStream, apis: &mut Vec<Api>, derived: &mut Vec<ApiItem>) -> Result<()> {
     while !input.is_empty() {
         let mut ocaml_rust = false;
         let attrs = input.call(Attribute::parse_outer)?;
@@ -36,6 +39,19 @@ fn parse(input: ParseStream, apis: &mut Vec<Api>) -> Result<()> {
             if ocaml_rust {
                 return Err(Error::new_spanned(item, "expected a module"));
             }
+            if attrs.iter().any(attr_derives_value) {
+                match item {
+                    Item::Struct(mut item) => {
+                        item.attrs.splice(..0, attrs);
+                        derived.push(ApiItem::Struct(item))
+                    }
+                    Item::Enum(mut item) => {
+                        item.attrs.splice(..0, attrs);
+                        derived.push(ApiItem::Enum(item))
+                    }
+                    _ => {}
+                }
+            }
             continue;
         }
 
@@ -50,9 +66,39 @@ fn parse(input: ParseStream, apis: &mut Vec<Api>) -> Result<()> {
             if semi.is_none() {
                 let content;
                 braced!(content in input);
-                parse(&content, apis)?;
+                parse(&content, apis, derived)?;
             }
         }
     }
     Ok(())
 }
+
+// Returns true for `#[derive(..)]` attributes that include `ToValue` or
+// `FromValue`, possibly with a path prefix like `ocaml_rust::ToValue`.
+#[allow(dead_code)]
+fn attr_derives_value(attr: &Attribute) -> bool {
+    attr_derives(attr, "ToValue") || attr_derives(attr, "FromValue")
+}
+
+fn attr_derives(attr: &Attribute, name: &str) -> bool {
+    if !attr.path.is_ident("derive") {
+        return false;
+    }
+    let paths = attr.parse_args_with(
+        syn::punctuated::Punctuated::<syn::Path, syn::Token![,]>::parse_terminated,
+    );
+    match paths {
+        Ok(paths) => paths.iter().any(|path| match path.segments.last() {
+            Some(segment) => segment.ident == name,
+            None => false,
+        }),
+        Err(_) => false,
+    }
+}
+
+/// Whether a derived struct or enum derives `ToValue`, otherwise it derives
+/// `FromValue`.
+#[allow(dead_code)]
+pub fn derives_to_value(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| attr_derives(attr, "ToValue"))
+}
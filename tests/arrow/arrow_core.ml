@@ -347,8 +347,8 @@ module Column = struct
 
   module Bigarray = struct
     type t =
-      | Int32 of (int, Bigarray.int32_elt) ba
-      | Int64 of (int, Bigarray.int64_elt) ba
+      | Int32 of (int32, Bigarray.int32_elt) ba
+      | Int64 of (int64, Bigarray.int64_elt) ba
       | Float32 of (float, Bigarray.float32_elt) ba
       | Float64 of (float, Bigarray.float64_elt) ba
 
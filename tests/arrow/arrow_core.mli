@@ -65,16 +65,16 @@ module Column : sig
     val int32 : int array -> int t
     val float64 : float array -> float t
     val float32 : float array -> float t
-    val int64_ba : (int, Bigarray.int64_elt) ba -> int t
-    val int32_ba : (int, Bigarray.int32_elt) ba -> int t
+    val int64_ba : (int64, Bigarray.int64_elt) ba -> int t
+    val int32_ba : (int32, Bigarray.int32_elt) ba -> int t
     val float64_ba : (float, Bigarray.float64_elt) ba -> float t
     val float32_ba : (float, Bigarray.float32_elt) ba -> float t
   end
 
   module Bigarray : sig
     type t =
-      | Int32 of (int, Bigarray.int32_elt) ba
-      | Int64 of (int, Bigarray.int64_elt) ba
+      | Int32 of (int32, Bigarray.int32_elt) ba
+      | Int64 of (int64, Bigarray.int64_elt) ba
       | Float32 of (float, Bigarray.float32_elt) ba
       | Float64 of (float, Bigarray.float64_elt) ba
 
@@ -1,3 +1,23 @@
+exception Rust_panic of string;;
+let () = Callback.register_exception "ocaml_rust.rust_panic" (Rust_panic "");;
+module Rust_error = struct
+  type kind =
+    | Io
+    | Parse
+    | Other
+
+  type t =
+    { kind : kind
+    ; message : string
+    ; sources : string array
+    }
+end
+
+exception Rust_error of Rust_error.t;;
+let () = Callback.register_exception "ocaml_rust.rust_error" (Rust_error { Rust_error.kind = Other; message = ""; sources = [||] });;
+type ('a, 'b) rust_fn1 = 'a -> 'b;;
+external _rust_fn1_call : 'f -> 'a -> 'b = "__ocaml_rust_fn1_call";;
+let () = Callback.register "ocaml_rust.rust_fn1" (fun f x -> _rust_fn1_call f x);;
 module Arrow = struct
 open! Sexplib.Conv
   type file_reader;;
@@ -73,6 +93,11 @@ open! Sexplib.Conv
     fields: schema_field array;
     metadata: (string * string) array;
   } [@@boxed][@@deriving sexp];;
+  external _layout_fingerprint : unit -> string = "__ocaml_arrow__layout_fingerprint";;
+  let () =
+    let fingerprint = _layout_fingerprint () in
+    if not (String.equal fingerprint "97e4717887ad2c2b") then
+      failwith ("ocaml-rust: the layout of the types in Arrow differs between OCaml (97e4717887ad2c2b) and Rust (" ^ fingerprint ^ "), the bindings have to be regenerated");;
   external file_reader
     : string -> (file_reader, string) Result.t
     = "__ocaml_arrow_file_reader"
@@ -265,29 +290,29 @@ open! Sexplib.Conv
 
 module Array_char = struct
   external from_ba
-    : (char, Bigarray.int8_unsigned_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
+    : (int, Bigarray.int8_unsigned_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
     = "__ocaml_arrowarray_char__from_ba"
   ;;
 
   external from
-    : char array -> array_ref
+    : string -> array_ref
     = "__ocaml_arrowarray_char__from"
   ;;
 
   external values
-    : array_ref -> char -> char array option
+    : array_ref -> char -> string option
     = "__ocaml_arrowarray_char__values"
   ;;
 
   external values_ba
-    : array_ref -> char -> (char, Bigarray.int8_unsigned_elt, Bigarray.c_layout) Bigarray.Array1.t option
+    : array_ref -> char -> (int, Bigarray.int8_unsigned_elt, Bigarray.c_layout) Bigarray.Array1.t option
     = "__ocaml_arrowarray_char__values_ba"
   ;;
 
 end
 module Array_date32 = struct
   external from_ba
-    : (int, Bigarray.int32_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
+    : (int32, Bigarray.int32_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
     = "__ocaml_arrowarray_date32__from_ba"
   ;;
 
@@ -307,14 +332,14 @@ module Array_date32 = struct
   ;;
 
   external values_ba
-    : array_ref -> Int32.t -> (int, Bigarray.int32_elt, Bigarray.c_layout) Bigarray.Array1.t option
+    : array_ref -> Int32.t -> (int32, Bigarray.int32_elt, Bigarray.c_layout) Bigarray.Array1.t option
     = "__ocaml_arrowarray_date32__values_ba"
   ;;
 
 end
 module Array_date64 = struct
   external from_ba
-    : (int, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
+    : (int64, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
     = "__ocaml_arrowarray_date64__from_ba"
   ;;
 
@@ -334,14 +359,14 @@ module Array_date64 = struct
   ;;
 
   external values_ba
-    : array_ref -> Int64.t -> (int, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t option
+    : array_ref -> Int64.t -> (int64, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t option
     = "__ocaml_arrowarray_date64__values_ba"
   ;;
 
 end
 module Array_duration_ms = struct
   external from_ba
-    : (int, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
+    : (int64, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
     = "__ocaml_arrowarray_duration_ms__from_ba"
   ;;
 
@@ -361,14 +386,14 @@ module Array_duration_ms = struct
   ;;
 
   external values_ba
-    : array_ref -> Int64.t -> (int, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t option
+    : array_ref -> Int64.t -> (int64, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t option
     = "__ocaml_arrowarray_duration_ms__values_ba"
   ;;
 
 end
 module Array_duration_ns = struct
   external from_ba
-    : (int, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
+    : (int64, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
     = "__ocaml_arrowarray_duration_ns__from_ba"
   ;;
 
@@ -388,14 +413,14 @@ module Array_duration_ns = struct
   ;;
 
   external values_ba
-    : array_ref -> Int64.t -> (int, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t option
+    : array_ref -> Int64.t -> (int64, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t option
     = "__ocaml_arrowarray_duration_ns__values_ba"
   ;;
 
 end
 module Array_duration_s = struct
   external from_ba
-    : (int, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
+    : (int64, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
     = "__ocaml_arrowarray_duration_s__from_ba"
   ;;
 
@@ -415,14 +440,14 @@ module Array_duration_s = struct
   ;;
 
   external values_ba
-    : array_ref -> Int64.t -> (int, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t option
+    : array_ref -> Int64.t -> (int64, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t option
     = "__ocaml_arrowarray_duration_s__values_ba"
   ;;
 
 end
 module Array_duration_us = struct
   external from_ba
-    : (int, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
+    : (int64, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
     = "__ocaml_arrowarray_duration_us__from_ba"
   ;;
 
@@ -442,7 +467,7 @@ module Array_duration_us = struct
   ;;
 
   external values_ba
-    : array_ref -> Int64.t -> (int, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t option
+    : array_ref -> Int64.t -> (int64, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t option
     = "__ocaml_arrowarray_duration_us__values_ba"
   ;;
 
@@ -503,7 +528,7 @@ module Array_f64 = struct
 end
 module Array_i32 = struct
   external from_ba
-    : (int, Bigarray.int32_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
+    : (int32, Bigarray.int32_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
     = "__ocaml_arrowarray_i32__from_ba"
   ;;
 
@@ -523,14 +548,14 @@ module Array_i32 = struct
   ;;
 
   external values_ba
-    : array_ref -> Int32.t -> (int, Bigarray.int32_elt, Bigarray.c_layout) Bigarray.Array1.t option
+    : array_ref -> Int32.t -> (int32, Bigarray.int32_elt, Bigarray.c_layout) Bigarray.Array1.t option
     = "__ocaml_arrowarray_i32__values_ba"
   ;;
 
 end
 module Array_i64 = struct
   external from_ba
-    : (int, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
+    : (int64, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
     = "__ocaml_arrowarray_i64__from_ba"
   ;;
 
@@ -550,14 +575,14 @@ module Array_i64 = struct
   ;;
 
   external values_ba
-    : array_ref -> Int64.t -> (int, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t option
+    : array_ref -> Int64.t -> (int64, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t option
     = "__ocaml_arrowarray_i64__values_ba"
   ;;
 
 end
 module Array_time64_ns = struct
   external from_ba
-    : (int, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
+    : (int64, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
     = "__ocaml_arrowarray_time64_ns__from_ba"
   ;;
 
@@ -577,14 +602,14 @@ module Array_time64_ns = struct
   ;;
 
   external values_ba
-    : array_ref -> Int64.t -> (int, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t option
+    : array_ref -> Int64.t -> (int64, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t option
     = "__ocaml_arrowarray_time64_ns__values_ba"
   ;;
 
 end
 module Array_time64_us = struct
   external from_ba
-    : (int, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
+    : (int64, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
     = "__ocaml_arrowarray_time64_us__from_ba"
   ;;
 
@@ -604,14 +629,14 @@ module Array_time64_us = struct
   ;;
 
   external values_ba
-    : array_ref -> Int64.t -> (int, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t option
+    : array_ref -> Int64.t -> (int64, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t option
     = "__ocaml_arrowarray_time64_us__values_ba"
   ;;
 
 end
 module Array_timestamp_ms = struct
   external from_ba
-    : (int, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
+    : (int64, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
     = "__ocaml_arrowarray_timestamp_ms__from_ba"
   ;;
 
@@ -631,14 +656,14 @@ module Array_timestamp_ms = struct
   ;;
 
   external values_ba
-    : array_ref -> Int64.t -> (int, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t option
+    : array_ref -> Int64.t -> (int64, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t option
     = "__ocaml_arrowarray_timestamp_ms__values_ba"
   ;;
 
 end
 module Array_timestamp_ns = struct
   external from_ba
-    : (int, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
+    : (int64, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
     = "__ocaml_arrowarray_timestamp_ns__from_ba"
   ;;
 
@@ -658,14 +683,14 @@ module Array_timestamp_ns = struct
   ;;
 
   external values_ba
-    : array_ref -> Int64.t -> (int, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t option
+    : array_ref -> Int64.t -> (int64, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t option
     = "__ocaml_arrowarray_timestamp_ns__values_ba"
   ;;
 
 end
 module Array_timestamp_s = struct
   external from_ba
-    : (int, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
+    : (int64, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
     = "__ocaml_arrowarray_timestamp_s__from_ba"
   ;;
 
@@ -685,14 +710,14 @@ module Array_timestamp_s = struct
   ;;
 
   external values_ba
-    : array_ref -> Int64.t -> (int, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t option
+    : array_ref -> Int64.t -> (int64, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t option
     = "__ocaml_arrowarray_timestamp_s__values_ba"
   ;;
 
 end
 module Array_timestamp_us = struct
   external from_ba
-    : (int, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
+    : (int64, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t -> array_ref
     = "__ocaml_arrowarray_timestamp_us__from_ba"
   ;;
 
@@ -712,7 +737,7 @@ module Array_timestamp_us = struct
   ;;
 
   external values_ba
-    : array_ref -> Int64.t -> (int, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t option
+    : array_ref -> Int64.t -> (int64, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t option
     = "__ocaml_arrowarray_timestamp_us__values_ba"
   ;;
 
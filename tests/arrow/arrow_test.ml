@@ -81,7 +81,7 @@ let%expect_test _ =
       let _column = Arrow.record_batch_column rb 123 in
       Stdio.printf "SHOULD HAVE FAILED!\n"
     with
-    | Failure str -> Stdio.printf "failed as expected, %s\n%!" (String.prefix str 68)
+    | Rust_panic str -> Stdio.printf "failed as expected, %s\n%!" (String.prefix str 68)
   in
   [%expect
     {| failed as expected, panicked at 'index out of bounds: the len is 3 but the index is 123' |}];
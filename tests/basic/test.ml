@@ -36,7 +36,17 @@ let%expect_test _ =
     = foo<42|-1337>: bar baz = |}];
   let v = Ffi.vec_add [| 3; 1; 4; 1; 5; 9; 2; 6; 5 |] (-1) in
   Stdio.print_s ([%sexp_of: int array] v);
-  [%expect {| (2 0 3 0 4 8 1 5 4) |}]
+  [%expect {| (2 0 3 0 4 8 1 5 4) |}];
+  Stdio.printf "%s\n%!" (Ffi.format6 1 (Int64.of_int 2) 3.5 "four" (Some 5) true);
+  [%expect {| 1 2 3.5 four Some(5) true |}];
+  Stdio.printf "%f %b %b\n%!" (Ffi.add_f64 1.25 2.5) (Ffi.is_even 42) (Ffi.is_even 1337);
+  [%expect {| 3.750000 true false |}];
+  (try
+     let (_ : int) = Ffi.rust_panic "boom" in
+     ()
+   with
+   | Rust_panic msg -> Stdio.printf "%s\n%!" msg);
+  [%expect {| panicked at 'boom', example/src/lib.rs:42:5 |}]
 
 let%expect_test _ =
   Stdio.printf "\n==== Test Struct ====\n";
@@ -97,12 +107,11 @@ let%expect_test _ =
     let (_ : int) = Ffi4.sum_n 1 (fun () -> failwith "ocaml-failwith") in
     ()
   with
-  | exn ->
-    Stdio.printf "failed as expected, %s\n%!" (Exn.to_string exn);
+  | Rust_panic msg ->
+    Stdio.printf "failed as expected, %s\n%!" msg;
     [%expect
       {|
-      failed as expected, (Failure
-        "panicked at 'called `Result::unwrap()` on an `Err` value: ocaml exn: Failure(\"ocaml-failwith\")', example/src/lib.rs:140:31") |}]
+      failed as expected, panicked at 'called `Result::unwrap()` on an `Err` value: ocaml exn: Failure("ocaml-failwith")', example/src/lib.rs:161:31 |}]
 
 let%expect_test _ =
   Stdio.printf "\n==== Test Custom Drop ====\n";
@@ -147,6 +156,591 @@ let%expect_test _ =
   Stdio.print_s ([%sexp_of: Ffi_double_array.quaternion] q_2);
   [%expect
     {|
-    ((a 1) (b 2) (c 0) (d -4))
+    ((a 1) (b 2) (c -3) (d -4))
     Quaternion { a: 3.14, b: 15.92, c: 65.35, d: 89.79 } Quaternion { a: 3.14, b: 15.92, c: 65.35, d: 89.79 }
-    ((a 6.28) (b 31.84) (c 130.69999694824219) (d 179.58)) |}]
+    ((a 6.28) (b 31.84) (c 130.7) (d 179.58)) |}];
+  let q = { Ffi_double_array.a = 1.; b = 2.; c = 2.; d = 4. } in
+  Stdio.printf "%.1f\n" (Ffi_double_array.quat_norm q);
+  Stdio.printf
+    "%.1f %.1f\n"
+    (Ffi_double_array.float_array_sum [||])
+    (Ffi_double_array.float_array_sum [| 1.5; 2.5; 3. |]);
+  let qs = [| 1.; 2.; 2.; 4.; 0.; 3.; 0.; 4. |] in
+  Ffi_double_array.normalize_quats qs;
+  Stdio.print_s ([%sexp_of: float array] qs);
+  (try Ffi_double_array.normalize_quats [| 1.; 2.; 3. |] with
+   | Invalid_argument msg -> Stdio.printf "invalid argument: %s\n" msg);
+  [%expect
+    {|
+    5.0
+    0.0 7.0
+    (0.2 0.4 0.4 0.8 0 0.6 0 0.8)
+    invalid argument: float array length 3 is not a multiple of 4 |}]
+
+let%expect_test _ =
+  Stdio.printf "\n==== Test Calling OCaml ====\n";
+  Ffi_ocaml.register_ocaml_format (Printf.sprintf "%d:%s");
+  Ffi_ocaml.register_ocaml_incr (fun x -> if x < 0 then failwith "negative" else x + 1);
+  Stdio.printf "%s\n%!" (Ffi_ocaml.call_ocaml_format 42);
+  Stdio.print_s (Ffi_ocaml.call_ocaml_incr 41 |> [%sexp_of: int res]);
+  Stdio.print_s (Ffi_ocaml.call_ocaml_incr (-1) |> [%sexp_of: int res]);
+  [%expect
+    {|
+    ==== Test Calling OCaml ====
+    42:from-rust
+    (Ok 42)
+    (Error "ocaml exn: Failure(\"negative\")") |}]
+
+let%expect_test _ =
+  Stdio.printf "\n==== Test Rust Errors ====\n";
+  let print_error (err : Rust_error.t) =
+    let kind =
+      match err.kind with
+      | Io -> "io"
+      | Parse -> "parse"
+      | Other -> "other"
+    in
+    Stdio.printf "%s: %s [%s]\n%!" kind err.message (String.concat ~sep:"; " (Array.to_list err.sources))
+  in
+  let print_result = function
+    | Ok v -> Stdio.printf "%d\n%!" v
+    | Error err -> print_error err
+  in
+  print_result (Ffi_errors.parse_int_record "42");
+  print_result (Ffi_errors.parse_int_record "foo");
+  (try Stdio.printf "%d\n%!" (Ffi_errors.parse_int_exn "1337") with
+  | Rust_error err -> print_error err);
+  (try Stdio.printf "%d\n%!" (Ffi_errors.parse_int_exn "bar") with
+  | Rust_error err -> print_error err);
+  (match Ffi_errors.read_config "/does/not/exist" with
+  | Ok _ -> Stdio.printf "unexpected success\n%!"
+  | Error err -> print_error err);
+  print_result (Ffi_errors.check_port 0);
+  Ffi_errors.register_error_kinds ();
+  print_result (Ffi_errors.check_port 0);
+  print_result (Ffi_errors.check_port 8080);
+  [%expect
+    {|
+    ==== Test Rust Errors ====
+    42
+    parse: invalid digit found in string []
+    1337
+    parse: invalid digit found in string []
+    io: cannot read config [No such file or directory (os error 2)]
+    other: invalid port 0 []
+    parse: invalid port 0 []
+    8080 |}]
+
+let%expect_test _ =
+  Stdio.printf "\n==== Test Lists ====\n";
+  Stdio.print_s ([%sexp_of: int list] (Ffi_list.list_add [ 1; 2; 3 ] 10));
+  Stdio.print_s ([%sexp_of: int list] (Ffi_list.list_add [] 10));
+  Stdio.print_s ([%sexp_of: float list] (Ffi_list.list_to_f32 [ 0.5; 1.25; -3. ]));
+  let series = { Ffi_list.name = "s"; values = [ 1.; 2.5; 4. ] } in
+  Stdio.print_s ([%sexp_of: Ffi_list.series] (Ffi_list.series_scale series 2.));
+  let shape = Ffi_list.Polygon [ 0., 0.; 1., 0.; 0., 1. ] in
+  Stdio.print_s ([%sexp_of: Ffi_list.shape] (Ffi_list.shape_translate shape 1. 2.));
+  Stdio.print_s ([%sexp_of: Ffi_list.shape] (Ffi_list.shape_translate Ffi_list.Empty 1. 2.));
+  Stdio.printf "%d\n" (Ffi_list.list_callback 5 (List.fold ~init:0 ~f:( + )));
+  [%expect
+    {|
+    ==== Test Lists ====
+    (11 12 13)
+    ()
+    (0.5 1.25 -3)
+    ((name s) (values (2 5 8)))
+    (Polygon ((1 2) (2 2) (1 3)))
+    Empty
+    10 |}]
+
+let%expect_test _ =
+  Stdio.printf "\n==== Test Multi-Argument Closures ====\n";
+  Stdio.printf
+    "%s\n"
+    (Ffi_closures.fold_left [| 1; 2; 3 |] "acc" (fun acc v -> Printf.sprintf "(%s+%d)" acc v));
+  Stdio.printf
+    "%s\n"
+    (Ffi_closures.call_with4 (fun i f s b -> Printf.sprintf "%d %.2f %s %b" i f s b));
+  [%expect
+    {|
+    ==== Test Multi-Argument Closures ====
+    (((acc+1)+2)+3)
+    42 2.50 foo true |}]
+
+let%expect_test _ =
+  Stdio.printf "\n==== Test Rust Closures ====\n";
+  let next = Ffi_rust_fn.counter 10 in
+  let v1 = next () in
+  let v2 = next () in
+  let v3 = next () in
+  Stdio.printf "%d %d %d\n" v1 v2 v3;
+  let split = Ffi_rust_fn.splitter ", " in
+  Stdio.print_s ([%sexp_of: string array] (split "a, b, c"));
+  let cmp = Ffi_rust_fn.comparator true in
+  let sorted = List.sort [ 3; 1; 4; 1; 5; 9; 2 ] ~compare:(fun x y -> cmp (x, y)) in
+  Stdio.print_s ([%sexp_of: int list] sorted);
+  Caml.Gc.full_major ();
+  Stdio.printf "%d\n" (next ());
+  let sqrt = Ffi_rust_fn.checked_sqrt () in
+  (try Stdio.printf "%f\n" (sqrt (-1.)) with
+  | Rust_panic msg -> Stdio.printf "%s\n" msg);
+  Stdio.printf "%f\n" (sqrt 4.);
+  let recurse = ref (fun x -> x) in
+  let f =
+    Ffi_rust_fn.incr_after (fun x ->
+      try !recurse x with
+      | Rust_panic msg ->
+        Stdio.printf "%s\n" msg;
+        x * 10)
+  in
+  recurse := f;
+  Stdio.printf "%d\n" (f 4);
+  [%expect
+    {|
+    ==== Test Rust Closures ====
+    10 11 12
+    (a b c)
+    (9 5 4 3 2 1 1)
+    13
+    panicked at 'negative input', example/src/lib.rs:488:13
+    2.000000
+    panicked at 'a RustFn1 closure cannot be called while it is already running', src/closure.rs:336:17
+    41 |}]
+
+exception Foo of int
+
+let () = Caml.Callback.register_exception "test.foo" (Foo 0)
+
+let%expect_test _ =
+  Stdio.printf "\n==== Test OCaml Exceptions ====\n";
+  Stdio.printf "%s\n" (Ffi_exn.describe_exn (fun () -> ()));
+  Stdio.printf "%s\n" (Ffi_exn.describe_exn (fun () -> Caml.raise Caml.Not_found));
+  Stdio.printf "%s\n" (Ffi_exn.describe_exn (fun () -> Caml.raise (Foo 42)));
+  Stdio.printf "%s\n" (Ffi_exn.describe_exn (fun () -> Caml.invalid_arg "bar"));
+  let f x = if x < 0 then Caml.raise (Foo x) else x + 1 in
+  (match Ffi_exn.double_or_raise f 20 with
+  | v -> Stdio.printf "%d\n" v
+  | exception Foo x -> Stdio.printf "Foo %d\n" x);
+  (match Ffi_exn.double_or_raise f (-3) with
+  | v -> Stdio.printf "%d\n" v
+  | exception Foo x -> Stdio.printf "Foo %d\n" x);
+  [%expect
+    {|
+    ==== Test OCaml Exceptions ====
+    no exception
+    Not_found args=0
+    Foo args=1 payload=42
+    Invalid_argument args=1
+    42
+    Foo -3 |}]
+
+let%expect_test _ =
+  Stdio.printf "\n==== Test Derived Types ====\n";
+  let print_vec2 { x; y } = Stdio.printf "%.2f %.2f\n" x y in
+  let v = { x = 1.; y = 2. } in
+  print_vec2 (Ffi_derive.vec2_move v North 1.);
+  print_vec2 (Ffi_derive.vec2_move v West 3.);
+  print_vec2 (Ffi_derive.vec2_move v (Angle 0.) 0.5);
+  let l = Ffi_derive.located_move { at = v; value = "here" } East in
+  Stdio.printf "%s at %.2f %.2f\n" l.value l.at.x l.at.y;
+  List.iter
+    [ { x = 0.; y = 2. }; { x = -1.; y = 0. }; { x = 1.; y = 1. } ]
+    ~f:(fun v ->
+      match Ffi_derive.vec2_direction v with
+      | North -> Stdio.printf "north\n"
+      | East -> Stdio.printf "east\n"
+      | South -> Stdio.printf "south\n"
+      | West -> Stdio.printf "west\n"
+      | Angle a -> Stdio.printf "angle %.4f\n" a);
+  [%expect
+    {|
+    ==== Test Derived Types ====
+    1.00 3.00
+    -2.00 2.00
+    1.50 2.00
+    here at 2.00 2.00
+    north
+    west
+    angle 0.7854 |}]
+
+let%expect_test _ =
+  Stdio.printf "\n==== Test Newtypes ====\n";
+  let (Ffi_newtypes.Meters m) =
+    Ffi_newtypes.meters_add (Ffi_newtypes.Meters 1.5) (Ffi_newtypes.Meters 2.25)
+  in
+  Stdio.printf "%.2f\n" m;
+  let (Ffi_newtypes.Meters m) = Ffi_newtypes.meters_double (Ffi_newtypes.Meters m) in
+  Stdio.printf "%.2f\n" m;
+  Stdio.printf
+    "%.2f\n"
+    (Ffi_newtypes.speed (Ffi_newtypes.Meters 10.) (Ffi_newtypes.Seconds 4.));
+  Ffi_newtypes.user_ids (Ffi_newtypes.UserId 41) 3
+  |> Array.iter ~f:(fun (Ffi_newtypes.UserId id) -> Stdio.printf "%d\n" id);
+  let (Ffi_newtypes.Pair (x, s)) = Ffi_newtypes.pair_swap (Ffi_newtypes.Pair (42, "abc")) in
+  Stdio.printf "%d %s\n" x s;
+  Stdio.printf "%s\n" (Ffi_newtypes.marker_name (Ffi_newtypes.marker_new ()));
+  [%expect
+    {|
+    ==== Test Newtypes ====
+    3.75
+    7.50
+    2.50
+    41
+    42
+    43
+    -42 cba
+    Marker |}]
+
+let%expect_test _ =
+  Stdio.printf "\n==== Test Generic Types ====\n";
+  let t = Ffi_generics.tagged_int "answer" 42 in
+  Stdio.printf "%s %d\n" t.Ffi_generics.tag t.value;
+  Stdio.printf
+    "%s\n"
+    (Ffi_generics.tagged_describe { Ffi_generics.tag = "floats"; value = [| 1.5; 2. |] });
+  let tree = Ffi_generics.tree_of_list [| 5; 3; 8; 1 |] in
+  let rec to_string = function
+    | Ffi_generics.Leaf -> "."
+    | Ffi_generics.Node (l, x, r) -> Printf.sprintf "(%s %d %s)" (to_string l) x (to_string r)
+  in
+  Stdio.printf "%s %d\n" (to_string tree) (Ffi_generics.tree_sum tree);
+  let strings = Ffi_generics.(Node (Leaf, "ab", Node (Leaf, "cde", Leaf))) in
+  let rec to_string = function
+    | Ffi_generics.Leaf -> "."
+    | Ffi_generics.Node (l, { Ffi_generics.tag; value }, r) ->
+      Printf.sprintf "(%s %s:%d %s)" (to_string l) tag value (to_string r)
+  in
+  Stdio.printf "%s\n" (to_string (Ffi_generics.tree_lengths strings));
+  [%expect
+    {|
+    ==== Test Generic Types ====
+    answer 42
+    floats: [1.5, 2.0]
+    (((. 1 .) 3 .) 5 (. 8 .)) 17
+    (. ab:2 (. cde:3 .)) |}]
+
+let%expect_test _ =
+  Stdio.printf "\n==== Test Polymorphic Variants ====\n";
+  Stdio.print_s
+    ([%sexp_of: Ffi_polyvar.open_flag array] (Ffi_polyvar.open_flags_default ()));
+  Stdio.printf
+    "%s\n"
+    (Ffi_polyvar.open_flags_describe [| `Write; `Rename ("a", "b"); `Append |]);
+  List.iter
+    [ `Read; `Write; `Append; `Mode 1; `Rename ("a", "b") ]
+    ~f:(fun flag ->
+      match Ffi_polyvar.open_flag_bump flag with
+      | `Rename (src, dst) -> Stdio.printf "rename %s %s\n" src dst
+      | flag -> Stdio.print_s ([%sexp_of: Ffi_polyvar.open_flag] flag));
+  [%expect
+    {|
+    ==== Test Polymorphic Variants ====
+    (Read (Mode 420))
+    Write,Rename("a", "b"),Append
+    Write
+    Append
+    Read
+    (Mode 2)
+    rename b a |}]
+
+let%expect_test _ =
+  Stdio.printf "\n==== Test Maps and Sets ====\n";
+  let counts = Ffi_maps.word_counts "b a c a b a" in
+  Stdio.print_s ([%sexp_of: (string * int) list] counts);
+  Stdio.print_s
+    ([%sexp_of: (string * int) list]
+       (Ffi_maps.merge_counts [ "x", 1; "y", 2 ] [ "y", 3; "z", 4 ]));
+  Stdio.print_s ([%sexp_of: int list] (Ffi_maps.unique_sorted [| 3; 1; 2; 3; 1 |]));
+  Stdio.printf
+    "%b %b\n"
+    (Ffi_maps.set_contains [ "a"; "b" ] "a")
+    (Ffi_maps.set_contains [ "a"; "b" ] "c");
+  let m = { Ffi_maps.name = "meta"; entries = [ "k1", "v1" ] } in
+  Stdio.print_s (Ffi_maps.sexp_of_metadata (Ffi_maps.metadata_set m "k0" "v0"));
+  let map = Rust_base.map_of_alist (module String) counts in
+  Stdio.printf "%d\n" (Map.find_exn map "a");
+  Stdio.print_s ([%sexp_of: (string * int) list] (Rust_base.map_to_alist map));
+  let set = Rust_base.set_of_list (module Int) (Ffi_maps.unique_sorted [| 2; 2; 1 |]) in
+  Stdio.printf "%d\n" (Set.length set);
+  let counts = Ffi_maps.With_base.word_counts "b a b" in
+  Stdio.printf "%d\n" (Map.find_exn counts "b");
+  let h = Hashtbl.of_alist_exn (module String) [ "x", 1 ] in
+  let merged = Ffi_maps.With_base.merge_counts h h in
+  Stdio.print_s ([%sexp_of: (string * int) list] (Map.to_alist merged));
+  let hash_set = Hash_set.of_list (module String) [ "a" ] in
+  Stdio.printf "%b\n" (Ffi_maps.With_base.set_contains hash_set "a");
+  (try
+     let (_ : (string * int) list) = Ffi_maps.merge_counts [ "x", 1; "x", 2 ] [] in
+     ()
+   with
+   | Invalid_argument msg -> Stdio.printf "%s\n" msg);
+  (try
+     let (_ : bool) = Ffi_maps.set_contains [ "a"; "a" ] "a" in
+     ()
+   with
+   | Invalid_argument msg -> Stdio.printf "%s\n" msg);
+  [%expect
+    {|
+    ==== Test Maps and Sets ====
+    ((a 3) (b 2) (c 1))
+    ((x 1) (y 5) (z 4))
+    (1 2 3)
+    true false
+    ((name meta) (entries ((k0 v0) (k1 v1))))
+    3
+    ((a 3) (b 2) (c 1))
+    2
+    2
+    ((x 2))
+    true
+    duplicate element in list converted to HashMap
+    duplicate element in list converted to HashSet |}]
+
+let%expect_test _ =
+  Stdio.printf "\n==== Test Bytes and Strings ====\n";
+  let b = Bytes.of_string "abc" in
+  Ffi_bytes.bytes_xor b 1;
+  Stdio.printf "%S\n" (Bytes.to_string b);
+  Ffi_bytes.bytes_xor b 1;
+  Stdio.printf "%S\n" (Bytes.to_string b);
+  Stdio.printf
+    "%d %d\n"
+    (Ffi_bytes.bytes_checksum "abc")
+    (Ffi_bytes.bytes_checksum "\xff\x00");
+  let dst = Bytes.make 3 '.' in
+  Stdio.printf "%d %S\n" (Ffi_bytes.bytes_copy "hello" dst) (Bytes.to_string dst);
+  let b = Bytes.of_string "xyz" in
+  (try Ffi_bytes.bytes_copy (Bytes.unsafe_to_string b) b |> Stdio.printf "%d\n" with
+   | Invalid_argument msg -> Stdio.printf "invalid argument: %s\n" msg);
+  let b = Ffi_bytes.bytes_reverse (Bytes.of_string "a\xffb\x00") in
+  Stdio.printf "%S\n" (Bytes.to_string b);
+  Stdio.printf "%d\n" (Ffi_bytes.lossy_length "h\xffi");
+  Stdio.printf "%d\n" (Ffi_bytes.utf8_length "h\xc3\xa9llo");
+  (try Ffi_bytes.utf8_length "\xff" |> Stdio.printf "%d\n" with
+   | Invalid_argument msg -> Stdio.printf "invalid argument: %s\n" msg);
+  Stdio.printf "%d\n" (Ffi_bytes.lossy_length "\xff");
+  Stdio.printf "%d\n" (Ffi_bytes.os_string_len "\xff\xfe");
+  Stdio.printf "%S\n" (Ffi_bytes.path_join "/tmp" "a\xffb");
+  [%expect
+    {|
+    ==== Test Bytes and Strings ====
+    "`cb"
+    "abc"
+    30833 7905
+    3 "hel"
+    invalid argument: src and dst must not alias
+    "\000b\255a"
+    3
+    5
+    invalid argument: invalid UTF-8 string: invalid utf-8 sequence of 1 bytes from index 0
+    1
+    2
+    "/tmp/a\255b" |}]
+
+let%expect_test _ =
+  Stdio.printf "\n==== Test Borrowed Arguments ====\n";
+  Stdio.printf "%d\n" (Ffi_borrowed.str_word_count "the quick  brown fox");
+  Stdio.printf
+    "%b %b\n"
+    (Ffi_borrowed.str_contains "h\xc3\xa9llo" "\xc3\xa9l")
+    (Ffi_borrowed.str_contains "hello" "world");
+  (try Ffi_borrowed.str_word_count "a\xff" |> Stdio.printf "%d\n" with
+   | Invalid_argument msg -> Stdio.printf "invalid argument: %s\n" msg);
+  Stdio.printf
+    "%.1f %.1f\n"
+    (Ffi_borrowed.floats_sum [||])
+    (Ffi_borrowed.floats_sum [| 1.5; 2.; 3.25 |]);
+  let xs = [| 1.; 2.5; -4. |] in
+  Ffi_borrowed.floats_scale xs 2.;
+  Stdio.print_s ([%sexp_of: float array] xs);
+  let xs = [| 1; -2; 40 |] in
+  Stdio.printf "%d\n" (Ffi_borrowed.ints_sum xs);
+  Ffi_borrowed.ints_incr xs;
+  Stdio.print_s ([%sexp_of: int array] xs);
+  (try Stdio.printf "%d\n" (Ffi_borrowed.str_len_with "abc" (fun x -> x + 1)) with
+  | Rust_panic msg -> Stdio.printf "%s\n" msg);
+  (try Stdio.printf "%d\n" (Ffi_borrowed.str_to_rooted "abc") with
+  | Rust_panic msg -> Stdio.printf "%s\n" msg);
+  [%expect
+    {|
+    ==== Test Borrowed Arguments ====
+    4
+    true false
+    invalid argument: invalid UTF-8 string: invalid utf-8 sequence of 1 bytes from index 1
+    0.0 6.8
+    (2 5 -8)
+    39
+    (2 -1 41)
+    panicked at 'cannot call an OCaml closure while borrowed arguments are live', src/borrowed.rs:238:9
+    panicked at 'cannot allocate OCaml values while borrowed arguments are live', src/borrowed.rs:238:9 |}]
+
+let%expect_test _ =
+  Stdio.printf "\n==== Test Bigarrays ====\n";
+  let m =
+    Bigarray.Array2.of_array
+      Bigarray.Float64
+      Bigarray.C_layout
+      [| [| 1.; 2.; 3. |]; [| 4.; 5.; 6. |] |]
+  in
+  let t = Ffi_bigarray.matrix_transpose m in
+  Stdio.printf
+    "%d %d %.0f %.0f\n"
+    (Bigarray.Array2.dim1 t)
+    (Bigarray.Array2.dim2 t)
+    t.{0, 1}
+    t.{2, 0};
+  let f =
+    Bigarray.Array2.of_array
+      Bigarray.Float64
+      Bigarray.Fortran_layout
+      [| [| 1.; 2. |]; [| 3.; 4. |] |]
+  in
+  Stdio.printf "%.0f\n" (Ffi_bigarray.matrix_trace f);
+  Stdio.print_s ([%sexp_of: int array] (Ffi_bigarray.matrix_strides f));
+  let c = Ffi_bigarray.cube_new 2 in
+  Stdio.printf "%.0f %.0f %.0f\n" c.{1, 1, 1} c.{2, 1, 2} c.{1, 2, 1};
+  let g = Bigarray.Genarray.create Bigarray.Float64 Bigarray.C_layout [| 2; 3; 4 |] in
+  Bigarray.Genarray.fill g 0.5;
+  Stdio.print_s ([%sexp_of: int array] (Ffi_bigarray.genarray_shape g));
+  Stdio.printf "%.1f\n" (Ffi_bigarray.genarray_sum g);
+  (match Ffi_bigarray.matrix_row_sums f with
+   | [| r0; r1 |] -> Stdio.printf "%.0f %.0f\n" r0 r1
+   | _ -> Stdio.printf "unexpected row sums\n");
+  Ffi_bigarray.genarray_scale g 2.;
+  Stdio.printf
+    "%.1f %.1f\n"
+    (Bigarray.Genarray.get g [| 1; 2; 3 |])
+    (Ffi_bigarray.genarray_sum g);
+  let empty = Bigarray.Genarray.create Bigarray.Float64 Bigarray.C_layout [| 0; 3 |] in
+  Stdio.printf "%.1f\n" (Ffi_bigarray.genarray_sum empty);
+  (try Stdio.printf "%.0f\n" (Ffi_bigarray.matrix_trace (Obj.magic m)) with
+   | Invalid_argument msg -> Stdio.printf "invalid argument: %s\n" msg);
+  [%expect
+    {|
+    ==== Test Bigarrays ====
+    3 2 4 3
+    5
+    (1 2)
+    0 101 10
+    (2 3 4)
+    12.0
+    3 7
+    1.0 24.0
+    0.0
+    invalid argument: unexpected layout for bigarray 0 |}]
+
+let%expect_test _ =
+  Stdio.printf "\n==== Test Bigarray Kinds ====\n";
+  let of_array kind xs = Bigarray.Array1.of_array kind Bigarray.C_layout xs in
+  let to_list ba = List.init (Bigarray.Array1.dim ba) ~f:(fun i -> ba.{i}) in
+  let xs = of_array Bigarray.Int16_signed [| -3; 1; 5; 1 |] in
+  Stdio.printf "%.2f\n" (Ffi_bigarray_kinds.int16_mean xs);
+  let xs = of_array Bigarray.Int16_unsigned [| 1; 65535; 3 |] in
+  Stdio.printf "%d\n" (Ffi_bigarray_kinds.uint16_max xs);
+  let xs = of_array Bigarray.Int8_signed [| -128; -5; 7 |] in
+  Stdio.print_s ([%sexp_of: int list] (to_list (Ffi_bigarray_kinds.int8_abs xs)));
+  let xs = of_array Bigarray.Int64 [| 1L; 2L; 40L |] in
+  Stdio.printf "%Ld\n" (Ffi_bigarray_kinds.int64_sum xs);
+  let xs =
+    of_array
+      Bigarray.Complex32
+      [| { Complex.re = 3.; im = 4. }; { Complex.re = 1.; im = -1. } |]
+  in
+  Stdio.print_s ([%sexp_of: float list] (to_list (Ffi_bigarray_kinds.iq_power xs)));
+  let xs = of_array Bigarray.Complex64 [| { Complex.re = 1.; im = 2. } |] in
+  List.iter (to_list (Ffi_bigarray_kinds.complex64_conj xs)) ~f:(fun c ->
+    Stdio.printf "%.0f %.0f\n" c.Complex.re c.Complex.im);
+  let xs = of_array Bigarray.Char [| 'a'; 'B'; 'z' |] in
+  Stdio.printf
+    "%s\n"
+    (String.of_char_list (to_list (Ffi_bigarray_kinds.chars_uppercase xs)));
+  let xs = of_array Bigarray.Int [| 1; -2 |] in
+  Stdio.printf
+    "%s\n"
+    (List.map (to_list (Ffi_bigarray_kinds.ints_double xs)) ~f:Nativeint.to_string
+     |> String.concat ~sep:" ");
+  [%expect
+    {|
+    ==== Test Bigarray Kinds ====
+    1.00
+    65535
+    (-128 5 7)
+    43
+    (25 2)
+    1 -2
+    ABZ
+    2 -4 |}]
+
+let%expect_test _ =
+  Stdio.printf "\n==== Test Owned Bigarrays ====\n";
+  let to_list ba = List.init (Bigarray.Array1.dim ba) ~f:(fun i -> ba.{i}) in
+  Stdio.print_s ([%sexp_of: float list] (to_list (Ffi_bigarray_owned.range_vec 5)));
+  Stdio.print_s ([%sexp_of: int64 list] (to_list (Ffi_bigarray_owned.squares_boxed 4)));
+  let m = Ffi_bigarray_owned.arc_matrix 2 3 in
+  Stdio.printf
+    "%d %d %.0f\n"
+    (Bigarray.Array2.dim1 m)
+    (Bigarray.Array2.dim2 m)
+    m.{1, 2};
+  let drops = Ffi_bigarray_owned.tracked_drops () in
+  let sum =
+    let ba = Ffi_bigarray_owned.tracked_buffer 1000 in
+    ba.{0} <- 2.5;
+    List.fold (to_list ba) ~init:0. ~f:( +. )
+  in
+  Caml.Gc.full_major ();
+  Stdio.printf "%.1f %d\n" sum (Ffi_bigarray_owned.tracked_drops () - drops);
+  let drops = Ffi_bigarray_owned.tracked_drops () in
+  let sum =
+    let sub = Bigarray.Array1.sub (Ffi_bigarray_owned.tracked_buffer 100) 10 5 in
+    Caml.Gc.full_major ();
+    let live_drops = Ffi_bigarray_owned.tracked_drops () - drops in
+    sub.{0} <- 3.;
+    Stdio.printf "%d\n" live_drops;
+    List.fold (to_list sub) ~init:0. ~f:( +. )
+  in
+  Caml.Gc.full_major ();
+  Stdio.printf "%.1f %d\n" sum (Ffi_bigarray_owned.tracked_drops () - drops);
+  [%expect
+    {|
+    ==== Test Owned Bigarrays ====
+    (0 1 2 3 4)
+    (0 1 4 9)
+    2 3 5
+    1501.0 1
+    0
+    9.0 1 |}]
+
+let%expect_test _ =
+  Stdio.printf "\n==== Test Bigarrays Without the Runtime Lock ====\n";
+  let of_array xs = Bigarray.Array1.of_array Bigarray.Float64 Bigarray.C_layout xs in
+  let to_list ba = List.init (Bigarray.Array1.dim ba) ~f:(fun i -> ba.{i}) in
+  let xs = of_array (Array.init 10 ~f:Float.of_int) in
+  Ffi_bigarray_parallel.bigarray_scale xs 2.;
+  Stdio.print_s ([%sexp_of: float list] (to_list xs));
+  let a = of_array [| 1.; 2.; 3.; 4. |] in
+  let sub = Bigarray.Array1.sub in
+  Ffi_bigarray_parallel.bigarray_add (sub a 0 2) (sub a 2 2);
+  Stdio.print_s ([%sexp_of: float list] (to_list a));
+  let add dst src =
+    try Ffi_bigarray_parallel.bigarray_add dst src with
+    | Invalid_argument msg -> Stdio.printf "invalid argument: %s\n" msg
+  in
+  add a a;
+  add (sub a 0 3) (sub a 2 2);
+  add (sub a 0 2) (of_array [| 10.; 20. |]);
+  Stdio.print_s ([%sexp_of: float list] (to_list a));
+  (try Ffi_bigarray.bigarray_copy a a with
+   | Invalid_argument msg -> Stdio.printf "invalid argument: %s\n" msg);
+  Ffi_bigarray.bigarray_copy (sub a 2 2) (sub a 0 2);
+  Stdio.print_s ([%sexp_of: float list] (to_list a));
+  [%expect
+    {|
+    ==== Test Bigarrays Without the Runtime Lock ====
+    (0 2 4 6 8 10 12 14 16 18)
+    (4 6 3 4)
+    invalid argument: dst and src must not overlap
+    invalid argument: dst and src must not overlap
+    (14 26 3 4)
+    invalid argument: dst and src must not overlap
+    (14 26 14 26) |}]
@@ -1,7 +1,83 @@
+exception Rust_panic of string;;
+let () = Callback.register_exception "ocaml_rust.rust_panic" (Rust_panic "");;
+module Rust_error = struct
+  type kind =
+    | Io
+    | Parse
+    | Other
+
+  type t =
+    { kind : kind
+    ; message : string
+    ; sources : string array
+    }
+end
+
+exception Rust_error of Rust_error.t;;
+let () = Callback.register_exception "ocaml_rust.rust_error" (Rust_error { Rust_error.kind = Other; message = ""; sources = [||] });;
+type ('a, 'b) rust_fn1 = 'a -> 'b;;
+external _rust_fn1_call : 'f -> 'a -> 'b = "__ocaml_rust_fn1_call";;
+let () = Callback.register "ocaml_rust.rust_fn1" (fun f x -> _rust_fn1_call f x);;
+module Rust_base = struct
+  let map_of_alist m l = Base.Map.of_alist_exn m l
+  let map_to_alist m = Base.Map.to_alist m
+  let hashtbl_of_alist m l = Base.Hashtbl.of_alist_exn m l
+  let hashtbl_to_alist t = Base.Hashtbl.to_alist t
+  let set_of_list m l = Base.Set.of_list m l
+  let set_to_list s = Base.Set.to_list s
+end
+
+(** A point in the plane, all-float records use the flat float layout. *)
+type vec2 = {
+  x: float;
+  y: float;
+} [@@boxed];;
+external _layout_fingerprint : unit -> string = "__ocaml_rust_to_value_ocaml_rust_example__Vec2__layout_fingerprint";;
+let () =
+  let fingerprint = _layout_fingerprint () in
+  if not (String.equal fingerprint "96122bb6a00b6d03") then
+    failwith ("ocaml-rust: the layout of type vec2 differs between OCaml (96122bb6a00b6d03) and Rust (" ^ fingerprint ^ "), the bindings have to be regenerated");;
+type direction =
+| North
+| East
+| South
+| West
+| Angle of float
+[@@boxed];;
+external _layout_fingerprint : unit -> string = "__ocaml_rust_to_value_ocaml_rust_example__Direction__layout_fingerprint";;
+let () =
+  let fingerprint = _layout_fingerprint () in
+  if not (String.equal fingerprint "7f56a47d49610b79") then
+    failwith ("ocaml-rust: the layout of type direction differs between OCaml (7f56a47d49610b79) and Rust (" ^ fingerprint ^ "), the bindings have to be regenerated");;
+type 't located = {
+  at: vec2;
+  value: 't;
+} [@@boxed];;
+external _layout_fingerprint : unit -> string = "__ocaml_rust_to_value_ocaml_rust_example__Located__layout_fingerprint";;
+let () =
+  let fingerprint = _layout_fingerprint () in
+  if not (String.equal fingerprint "059e0fec4ddf2b90") then
+    failwith ("ocaml-rust: the layout of type located differs between OCaml (059e0fec4ddf2b90) and Rust (" ^ fingerprint ^ "), the bindings have to be regenerated");;
 module Ffi = struct
+  external _layout_fingerprint : unit -> string = "__ocaml_ffi__layout_fingerprint";;
+  let () =
+    let fingerprint = _layout_fingerprint () in
+    if not (String.equal fingerprint "7007a7c04a98cc46") then
+      failwith ("ocaml-rust: the layout of the types in Ffi differs between OCaml (7007a7c04a98cc46) and Rust (" ^ fingerprint ^ "), the bindings have to be regenerated");;
   external add_i64
-    : Int64.t -> Int64.t -> Int64.t
-    = "__ocaml_ffi_add_i64"
+    : (Int64.t [@unboxed]) -> (Int64.t [@unboxed]) -> (Int64.t [@unboxed])
+    = "__ocaml_ffi_add_i64" "__ocaml_ffi_add_i64_unboxed"
+  ;;
+
+  external add_f64
+    : (float [@unboxed]) -> (float [@unboxed]) -> (float [@unboxed])
+    = "__ocaml_ffi_add_f64" "__ocaml_ffi_add_f64_unboxed"
+  [@@noalloc]
+  ;;
+
+  external is_even
+    : (int [@untagged]) -> bool
+    = "__ocaml_ffi_is_even" "__ocaml_ffi_is_even_unboxed"
   ;;
 
   external str_format
@@ -24,16 +100,31 @@ module Ffi = struct
     = "__ocaml_ffi_vec_add"
   ;;
 
+  external rust_panic
+    : string -> int
+    = "__ocaml_ffi_rust_panic"
+  ;;
+
+  external format6
+    : int -> Int64.t -> float -> string -> int option -> bool -> string
+    = "__ocaml_ffi_format6_bytecode" "__ocaml_ffi_format6"
+  ;;
+
 module Foo = struct
   external add_one
-    : int -> int
-    = "__ocaml_ffifoo__add_one"
+    : (int [@untagged]) -> (int [@untagged])
+    = "__ocaml_ffifoo__add_one" "__ocaml_ffifoo__add_one_unboxed"
   ;;
 
 end
 end
 module Ffi2 = struct
   type my_vec;;
+  external _layout_fingerprint : unit -> string = "__ocaml_ffi2__layout_fingerprint";;
+  let () =
+    let fingerprint = _layout_fingerprint () in
+    if not (String.equal fingerprint "06c990c878c20086") then
+      failwith ("ocaml-rust: the layout of the types in Ffi2 differs between OCaml (06c990c878c20086) and Rust (" ^ fingerprint ^ "), the bindings have to be regenerated");;
   external vec_new
     : unit -> my_vec
     = "__ocaml_ffi2_vec_new"
@@ -65,6 +156,11 @@ open! Sexplib.Conv
     z: (int * string option * float);
     zs: float array;
   } [@@boxed][@@deriving sexp];;
+  external _layout_fingerprint : unit -> string = "__ocaml_ffi3__layout_fingerprint";;
+  let () =
+    let fingerprint = _layout_fingerprint () in
+    if not (String.equal fingerprint "9e3f57a19e2b40b1") then
+      failwith ("ocaml-rust: the layout of the types in Ffi3 differs between OCaml (9e3f57a19e2b40b1) and Rust (" ^ fingerprint ^ "), the bindings have to be regenerated");;
   external mystruct_to_string
     : my_struct -> string
     = "__ocaml_ffi3_mystruct_to_string"
@@ -87,6 +183,11 @@ open! Sexplib.Conv
 
 end
 module Ffi4 = struct
+  external _layout_fingerprint : unit -> string = "__ocaml_ffi4__layout_fingerprint";;
+  let () =
+    let fingerprint = _layout_fingerprint () in
+    if not (String.equal fingerprint "e80c4eee4f4ca338") then
+      failwith ("ocaml-rust: the layout of the types in Ffi4 differs between OCaml (e80c4eee4f4ca338) and Rust (" ^ fingerprint ^ "), the bindings have to be regenerated");;
   external map_callback
     : int array -> ((int) -> (string)) -> string array
     = "__ocaml_ffi4_map_callback"
@@ -100,6 +201,11 @@ module Ffi4 = struct
 end
 module Ffi6 = struct
   type c;;
+  external _layout_fingerprint : unit -> string = "__ocaml_ffi6__layout_fingerprint";;
+  let () =
+    let fingerprint = _layout_fingerprint () in
+    if not (String.equal fingerprint "cfa4d8394e6e3a57") then
+      failwith ("ocaml-rust: the layout of the types in Ffi6 differs between OCaml (cfa4d8394e6e3a57) and Rust (" ^ fingerprint ^ "), the bindings have to be regenerated");;
   external create_foo2
     : int -> c
     = "__ocaml_ffi6_create_foo2"
@@ -113,6 +219,11 @@ module Ffi6 = struct
 end
 module Ffi7 = struct
   type compact;;
+  external _layout_fingerprint : unit -> string = "__ocaml_ffi7__layout_fingerprint";;
+  let () =
+    let fingerprint = _layout_fingerprint () in
+    if not (String.equal fingerprint "41949062ae04ceca") then
+      failwith ("ocaml-rust: the layout of the types in Ffi7 differs between OCaml (41949062ae04ceca) and Rust (" ^ fingerprint ^ "), the bindings have to be regenerated");;
   external generate
     : int -> ((((Int64.t * Int64.t) * compact) * Int64.t) * Int64.t)
     = "__ocaml_ffi7_generate"
@@ -127,6 +238,11 @@ open! Sexplib.Conv
     c: float;
     d: float;
   } [@@boxed][@@deriving sexp];;
+  external _layout_fingerprint : unit -> string = "__ocaml_ffi_double_array__layout_fingerprint";;
+  let () =
+    let fingerprint = _layout_fingerprint () in
+    if not (String.equal fingerprint "d66e9324fb3ab655") then
+      failwith ("ocaml-rust: the layout of the types in Ffi_double_array differs between OCaml (d66e9324fb3ab655) and Rust (" ^ fingerprint ^ "), the bindings have to be regenerated");;
   external add_ones
     : float array -> float array
     = "__ocaml_ffi_double_array_add_ones"
@@ -142,4 +258,627 @@ open! Sexplib.Conv
     = "__ocaml_ffi_double_array_create_quat"
   ;;
 
+  external quat_norm
+    : quaternion -> float
+    = "__ocaml_ffi_double_array_quat_norm"
+  ;;
+
+  external float_array_sum
+    : float array -> float
+    = "__ocaml_ffi_double_array_float_array_sum"
+  ;;
+
+  external normalize_quats
+    : float array -> unit
+    = "__ocaml_ffi_double_array_normalize_quats"
+  ;;
+
+end
+module Ffi_ocaml = struct
+  external _layout_fingerprint : unit -> string = "__ocaml_ffi_ocaml__layout_fingerprint";;
+  let () =
+    let fingerprint = _layout_fingerprint () in
+    if not (String.equal fingerprint "f032a03e52a9a87a") then
+      failwith ("ocaml-rust: the layout of the types in Ffi_ocaml differs between OCaml (f032a03e52a9a87a) and Rust (" ^ fingerprint ^ "), the bindings have to be regenerated");;
+  external call_ocaml_format
+    : int -> string
+    = "__ocaml_ffi_ocaml_call_ocaml_format"
+  ;;
+
+  external call_ocaml_incr
+    : int -> (int, string) Result.t
+    = "__ocaml_ffi_ocaml_call_ocaml_incr"
+  ;;
+
+  let register_ocaml_format (f : int -> string -> string) =
+    Callback.register "__ocaml_ffi_ocaml_ocaml_format" f
+  ;;
+
+
+  (** Increments its argument, this can raise an exception. *)
+  let register_ocaml_incr (f : int -> int) =
+    Callback.register "__ocaml_ffi_ocaml_ocaml_incr" f
+  ;;
+
+end
+module Ffi_errors = struct
+  external _layout_fingerprint : unit -> string = "__ocaml_ffi_errors__layout_fingerprint";;
+  let () =
+    let fingerprint = _layout_fingerprint () in
+    if not (String.equal fingerprint "127d115dc9638d80") then
+      failwith ("ocaml-rust: the layout of the types in Ffi_errors differs between OCaml (127d115dc9638d80) and Rust (" ^ fingerprint ^ "), the bindings have to be regenerated");;
+  external parse_int_record
+    : string -> (int, Rust_error.t) Result.t
+    = "__ocaml_ffi_errors_parse_int_record"
+  ;;
+
+  external parse_int_exn
+    : string -> int
+    = "__ocaml_ffi_errors_parse_int_exn"
+  ;;
+
+  external read_config
+    : string -> (string, Rust_error.t) Result.t
+    = "__ocaml_ffi_errors_read_config"
+  ;;
+
+
+  (** Checks that the port is in 1..65535 ( *inclusive* ), the error "kind"
+      depends on { |register_error_kinds|} having been called, '"' and "C:\\" *)
+  external check_port
+    : int -> (int, Rust_error.t) Result.t
+    = "__ocaml_ffi_errors_check_port"
+  ;;
+
+  external register_error_kinds
+    : unit -> unit
+    = "__ocaml_ffi_errors_register_error_kinds" "__ocaml_ffi_errors_register_error_kinds_unboxed"
+  ;;
+
+end
+module Ffi_list = struct
+  [@@@ocaml.alert "-deprecated"]
+open! Sexplib.Conv
+
+  (** A shape given by the list of its vertices. *)
+  type shape =
+  | Empty
+  | Polygon of (float * float) list (** The vertices are given in clockwise order. *)
+  [@@boxed][@@deriving sexp];;
+
+  (** A named series of values,
+      e.g. a column from a dataframe. *)
+  type series = {
+    name: string;
+    values: float list; (** Missing values are represented by nan. *)
+  } [@@boxed][@@deriving sexp];;
+  external _layout_fingerprint : unit -> string = "__ocaml_ffi_list__layout_fingerprint";;
+  let () =
+    let fingerprint = _layout_fingerprint () in
+    if not (String.equal fingerprint "5bcd8cbef535d15f") then
+      failwith ("ocaml-rust: the layout of the types in Ffi_list differs between OCaml (5bcd8cbef535d15f) and Rust (" ^ fingerprint ^ "), the bindings have to be regenerated");;
+
+  (** Adds `y` to all the elements of the list. *)
+  external list_add
+    : int list -> int -> int list
+    = "__ocaml_ffi_list_list_add"
+  ;;
+
+  external list_incr
+    : int list -> int list
+    = "__ocaml_ffi_list_list_incr"
+  [@@ocaml.deprecated "use list_add instead"]
+  ;;
+
+  external list_to_f32
+    : float list -> float list
+    = "__ocaml_ffi_list_list_to_f32"
+  ;;
+
+  external series_scale
+    : series -> float -> series
+    = "__ocaml_ffi_list_series_scale"
+  ;;
+
+  external shape_translate
+    : shape -> float -> float -> shape
+    = "__ocaml_ffi_list_shape_translate"
+  ;;
+
+  external list_callback
+    : int -> ((int list) -> (int)) -> int
+    = "__ocaml_ffi_list_list_callback"
+  ;;
+
+end
+module Ffi_closures = struct
+  external _layout_fingerprint : unit -> string = "__ocaml_ffi_closures__layout_fingerprint";;
+  let () =
+    let fingerprint = _layout_fingerprint () in
+    if not (String.equal fingerprint "1fcb6fb4c41ee9f6") then
+      failwith ("ocaml-rust: the layout of the types in Ffi_closures differs between OCaml (1fcb6fb4c41ee9f6) and Rust (" ^ fingerprint ^ "), the bindings have to be regenerated");;
+  external fold_left
+    : int array -> string -> ((string) -> (int) -> (string)) -> string
+    = "__ocaml_ffi_closures_fold_left"
+  ;;
+
+  external call_with4
+    : ((int) -> (float) -> (string) -> (bool) -> (string)) -> string
+    = "__ocaml_ffi_closures_call_with4"
+  ;;
+
+end
+module Ffi_rust_fn = struct
+  external _layout_fingerprint : unit -> string = "__ocaml_ffi_rust_fn__layout_fingerprint";;
+  let () =
+    let fingerprint = _layout_fingerprint () in
+    if not (String.equal fingerprint "d07bc193876d1f11") then
+      failwith ("ocaml-rust: the layout of the types in Ffi_rust_fn differs between OCaml (d07bc193876d1f11) and Rust (" ^ fingerprint ^ "), the bindings have to be regenerated");;
+  external counter
+    : int -> ((unit), (int)) rust_fn1
+    = "__ocaml_ffi_rust_fn_counter"
+  ;;
+
+  external splitter
+    : string -> ((string), (string array)) rust_fn1
+    = "__ocaml_ffi_rust_fn_splitter"
+  ;;
+
+  external comparator
+    : bool -> (((int * int)), (int)) rust_fn1
+    = "__ocaml_ffi_rust_fn_comparator"
+  ;;
+
+  external checked_sqrt
+    : unit -> ((float), (float)) rust_fn1
+    = "__ocaml_ffi_rust_fn_checked_sqrt"
+  ;;
+
+  external incr_after
+    : ((int) -> (int)) -> ((int), (int)) rust_fn1
+    = "__ocaml_ffi_rust_fn_incr_after"
+  ;;
+
+end
+module Ffi_exn = struct
+  external _layout_fingerprint : unit -> string = "__ocaml_ffi_exn__layout_fingerprint";;
+  let () =
+    let fingerprint = _layout_fingerprint () in
+    if not (String.equal fingerprint "caca95de3b5f7524") then
+      failwith ("ocaml-rust: the layout of the types in Ffi_exn differs between OCaml (caca95de3b5f7524) and Rust (" ^ fingerprint ^ "), the bindings have to be regenerated");;
+  external describe_exn
+    : (unit -> (unit)) -> string
+    = "__ocaml_ffi_exn_describe_exn"
+  ;;
+
+  external double_or_raise
+    : ((int) -> (int)) -> int -> int
+    = "__ocaml_ffi_exn_double_or_raise"
+  ;;
+
+end
+module Ffi_derive = struct
+  external _layout_fingerprint : unit -> string = "__ocaml_ffi_derive__layout_fingerprint";;
+  let () =
+    let fingerprint = _layout_fingerprint () in
+    if not (String.equal fingerprint "bf756da2c6b76249") then
+      failwith ("ocaml-rust: the layout of the types in Ffi_derive differs between OCaml (bf756da2c6b76249) and Rust (" ^ fingerprint ^ "), the bindings have to be regenerated");;
+  external vec2_move
+    : vec2 -> direction -> float -> vec2
+    = "__ocaml_ffi_derive_vec2_move"
+  ;;
+
+  external vec2_direction
+    : vec2 -> direction
+    = "__ocaml_ffi_derive_vec2_direction"
+  ;;
+
+  external located_move
+    : string located -> direction -> string located
+    = "__ocaml_ffi_derive_located_move"
+  ;;
+
+end
+module Ffi_newtypes = struct
+  type meters = Meters of float [@@unboxed];;
+  type user_id = UserId of int [@@unboxed];;
+  type seconds = Seconds of float [@@boxed];;
+  type pair = Pair of int * string [@@boxed];;
+  type marker = unit;;
+  external _layout_fingerprint : unit -> string = "__ocaml_ffi_newtypes__layout_fingerprint";;
+  let () =
+    let fingerprint = _layout_fingerprint () in
+    if not (String.equal fingerprint "dbec60ae41ca6c88") then
+      failwith ("ocaml-rust: the layout of the types in Ffi_newtypes differs between OCaml (dbec60ae41ca6c88) and Rust (" ^ fingerprint ^ "), the bindings have to be regenerated");;
+  external meters_add
+    : meters -> meters -> meters
+    = "__ocaml_ffi_newtypes_meters_add"
+  ;;
+
+  external meters_double
+    : meters -> meters
+    = "__ocaml_ffi_newtypes_meters_double"
+  ;;
+
+  external speed
+    : meters -> seconds -> float
+    = "__ocaml_ffi_newtypes_speed"
+  ;;
+
+  external user_ids
+    : user_id -> int -> user_id array
+    = "__ocaml_ffi_newtypes_user_ids"
+  ;;
+
+  external pair_swap
+    : pair -> pair
+    = "__ocaml_ffi_newtypes_pair_swap"
+  ;;
+
+  external marker_name
+    : marker -> string
+    = "__ocaml_ffi_newtypes_marker_name"
+  ;;
+
+  external marker_new
+    : unit -> marker
+    = "__ocaml_ffi_newtypes_marker_new"
+  ;;
+
+end
+module Ffi_generics = struct
+  type 't tagged = {
+    tag: string;
+    value: 't;
+  } [@@boxed];;
+  type 't tree =
+  | Leaf
+  | Node of 't tree * 't * 't tree
+  [@@boxed];;
+  external _layout_fingerprint : unit -> string = "__ocaml_ffi_generics__layout_fingerprint";;
+  let () =
+    let fingerprint = _layout_fingerprint () in
+    if not (String.equal fingerprint "7b587758bc762a31") then
+      failwith ("ocaml-rust: the layout of the types in Ffi_generics differs between OCaml (7b587758bc762a31) and Rust (" ^ fingerprint ^ "), the bindings have to be regenerated");;
+  external tagged_int
+    : string -> int -> int tagged
+    = "__ocaml_ffi_generics_tagged_int"
+  ;;
+
+  external tagged_describe
+    : float array tagged -> string
+    = "__ocaml_ffi_generics_tagged_describe"
+  ;;
+
+  external tree_of_list
+    : int array -> int tree
+    = "__ocaml_ffi_generics_tree_of_list"
+  ;;
+
+  external tree_sum
+    : int tree -> int
+    = "__ocaml_ffi_generics_tree_sum"
+  ;;
+
+  external tree_lengths
+    : string tree -> int tagged tree
+    = "__ocaml_ffi_generics_tree_lengths"
+  ;;
+
+end
+module Ffi_polyvar = struct
+open! Sexplib.Conv
+  type open_flag =
+  [ `Read
+  | `Write
+  | `Append
+  | `Mode of int
+  | `Rename of string * string
+  ] [@@deriving sexp];;
+  external _layout_fingerprint : unit -> string = "__ocaml_ffi_polyvar__layout_fingerprint";;
+  let () =
+    let fingerprint = _layout_fingerprint () in
+    if not (String.equal fingerprint "8f18659228f64d8e") then
+      failwith ("ocaml-rust: the layout of the types in Ffi_polyvar differs between OCaml (8f18659228f64d8e) and Rust (" ^ fingerprint ^ "), the bindings have to be regenerated");;
+  external open_flags_default
+    : unit -> open_flag array
+    = "__ocaml_ffi_polyvar_open_flags_default"
+  ;;
+
+  external open_flags_describe
+    : open_flag array -> string
+    = "__ocaml_ffi_polyvar_open_flags_describe"
+  ;;
+
+  external open_flag_bump
+    : open_flag -> open_flag
+    = "__ocaml_ffi_polyvar_open_flag_bump"
+  ;;
+
+end
+module Ffi_maps = struct
+open! Sexplib.Conv
+  type metadata = {
+    name: string;
+    entries: (string * string) list;
+  } [@@boxed][@@deriving sexp];;
+  external _layout_fingerprint : unit -> string = "__ocaml_ffi_maps__layout_fingerprint";;
+  let () =
+    let fingerprint = _layout_fingerprint () in
+    if not (String.equal fingerprint "e308e814d95416da") then
+      failwith ("ocaml-rust: the layout of the types in Ffi_maps differs between OCaml (e308e814d95416da) and Rust (" ^ fingerprint ^ "), the bindings have to be regenerated");;
+  external word_counts
+    : string -> (string * int) list
+    = "__ocaml_ffi_maps_word_counts"
+  ;;
+
+  external merge_counts
+    : (string * int) list -> (string * int) list -> (string * int) list
+    = "__ocaml_ffi_maps_merge_counts"
+  ;;
+
+  external unique_sorted
+    : int array -> int list
+    = "__ocaml_ffi_maps_unique_sorted"
+  ;;
+
+  external set_contains
+    : string list -> string -> bool
+    = "__ocaml_ffi_maps_set_contains"
+  ;;
+
+  external metadata_set
+    : metadata -> string -> string -> metadata
+    = "__ocaml_ffi_maps_metadata_set"
+  ;;
+
+  module With_base = struct
+    let word_counts x0 = Base.Map.of_alist_exn (module Base.String) (word_counts x0)
+    let merge_counts (x0 : (string, int) Base.Hashtbl.t) (x1 : (string, int) Base.Hashtbl.t) = Base.Map.of_alist_exn (module Base.String) (merge_counts (Base.Hashtbl.to_alist x0) (Base.Hashtbl.to_alist x1))
+    let unique_sorted x0 = Base.Set.of_list (module Base.Int) (unique_sorted x0)
+    let set_contains (x0 : string Base.Hash_set.t) x1 = set_contains (Base.Hash_set.to_list x0) x1
+  end;;
+
+end
+module Ffi_bytes = struct
+  external _layout_fingerprint : unit -> string = "__ocaml_ffi_bytes__layout_fingerprint";;
+  let () =
+    let fingerprint = _layout_fingerprint () in
+    if not (String.equal fingerprint "375b8f992b41f90c") then
+      failwith ("ocaml-rust: the layout of the types in Ffi_bytes differs between OCaml (375b8f992b41f90c) and Rust (" ^ fingerprint ^ "), the bindings have to be regenerated");;
+  external bytes_xor
+    : bytes -> int -> unit
+    = "__ocaml_ffi_bytes_bytes_xor"
+  ;;
+
+  external bytes_checksum
+    : string -> int
+    = "__ocaml_ffi_bytes_bytes_checksum"
+  ;;
+
+  external bytes_copy
+    : string -> bytes -> int
+    = "__ocaml_ffi_bytes_bytes_copy"
+  ;;
+
+  external bytes_reverse
+    : bytes -> bytes
+    = "__ocaml_ffi_bytes_bytes_reverse"
+  ;;
+
+  external lossy_length
+    : string -> int
+    = "__ocaml_ffi_bytes_lossy_length"
+  ;;
+
+  external utf8_length
+    : string -> int
+    = "__ocaml_ffi_bytes_utf8_length"
+  ;;
+
+  external os_string_len
+    : string -> int
+    = "__ocaml_ffi_bytes_os_string_len"
+  ;;
+
+  external path_join
+    : string -> string -> string
+    = "__ocaml_ffi_bytes_path_join"
+  ;;
+
+end
+module Ffi_borrowed = struct
+  external _layout_fingerprint : unit -> string = "__ocaml_ffi_borrowed__layout_fingerprint";;
+  let () =
+    let fingerprint = _layout_fingerprint () in
+    if not (String.equal fingerprint "bbb473082fb89847") then
+      failwith ("ocaml-rust: the layout of the types in Ffi_borrowed differs between OCaml (bbb473082fb89847) and Rust (" ^ fingerprint ^ "), the bindings have to be regenerated");;
+  external str_word_count
+    : string -> int
+    = "__ocaml_ffi_borrowed_str_word_count"
+  ;;
+
+  external str_contains
+    : string -> string -> bool
+    = "__ocaml_ffi_borrowed_str_contains"
+  ;;
+
+  external floats_sum
+    : float array -> float
+    = "__ocaml_ffi_borrowed_floats_sum"
+  ;;
+
+  external floats_scale
+    : float array -> float -> unit
+    = "__ocaml_ffi_borrowed_floats_scale"
+  ;;
+
+  external ints_sum
+    : int array -> int
+    = "__ocaml_ffi_borrowed_ints_sum"
+  ;;
+
+  external ints_incr
+    : int array -> unit
+    = "__ocaml_ffi_borrowed_ints_incr"
+  ;;
+
+  external str_len_with
+    : string -> ((int) -> (int)) -> int
+    = "__ocaml_ffi_borrowed_str_len_with"
+  ;;
+
+  external str_to_rooted
+    : string -> int
+    = "__ocaml_ffi_borrowed_str_to_rooted"
+  ;;
+
+end
+module Ffi_bigarray = struct
+  external _layout_fingerprint : unit -> string = "__ocaml_ffi_bigarray__layout_fingerprint";;
+  let () =
+    let fingerprint = _layout_fingerprint () in
+    if not (String.equal fingerprint "1ba322040e4566cf") then
+      failwith ("ocaml-rust: the layout of the types in Ffi_bigarray differs between OCaml (1ba322040e4566cf) and Rust (" ^ fingerprint ^ "), the bindings have to be regenerated");;
+  external matrix_transpose
+    : (float, Bigarray.float64_elt, Bigarray.c_layout) Bigarray.Array2.t -> (float, Bigarray.float64_elt, Bigarray.c_layout) Bigarray.Array2.t
+    = "__ocaml_ffi_bigarray_matrix_transpose"
+  ;;
+
+  external matrix_trace
+    : (float, Bigarray.float64_elt, Bigarray.fortran_layout) Bigarray.Array2.t -> float
+    = "__ocaml_ffi_bigarray_matrix_trace"
+  ;;
+
+  external matrix_strides
+    : (float, Bigarray.float64_elt, Bigarray.fortran_layout) Bigarray.Array2.t -> int array
+    = "__ocaml_ffi_bigarray_matrix_strides"
+  ;;
+
+  external cube_new
+    : int -> (float, Bigarray.float32_elt, Bigarray.fortran_layout) Bigarray.Array3.t
+    = "__ocaml_ffi_bigarray_cube_new"
+  ;;
+
+  external genarray_shape
+    : (float, Bigarray.float64_elt, Bigarray.c_layout) Bigarray.Genarray.t -> int array
+    = "__ocaml_ffi_bigarray_genarray_shape"
+  ;;
+
+  external genarray_sum
+    : (float, Bigarray.float64_elt, Bigarray.c_layout) Bigarray.Genarray.t -> float
+    = "__ocaml_ffi_bigarray_genarray_sum"
+  ;;
+
+  external matrix_row_sums
+    : (float, Bigarray.float64_elt, Bigarray.fortran_layout) Bigarray.Array2.t -> float array
+    = "__ocaml_ffi_bigarray_matrix_row_sums"
+  ;;
+
+  external genarray_scale
+    : (float, Bigarray.float64_elt, Bigarray.c_layout) Bigarray.Genarray.t -> float -> unit
+    = "__ocaml_ffi_bigarray_genarray_scale"
+  ;;
+
+  external bigarray_copy
+    : (float, Bigarray.float64_elt, Bigarray.c_layout) Bigarray.Array1.t -> (float, Bigarray.float64_elt, Bigarray.c_layout) Bigarray.Array1.t -> unit
+    = "__ocaml_ffi_bigarray_bigarray_copy"
+  ;;
+
+end
+module Ffi_bigarray_kinds = struct
+  external _layout_fingerprint : unit -> string = "__ocaml_ffi_bigarray_kinds__layout_fingerprint";;
+  let () =
+    let fingerprint = _layout_fingerprint () in
+    if not (String.equal fingerprint "509a5ee8f445f841") then
+      failwith ("ocaml-rust: the layout of the types in Ffi_bigarray_kinds differs between OCaml (509a5ee8f445f841) and Rust (" ^ fingerprint ^ "), the bindings have to be regenerated");;
+  external int16_mean
+    : (int, Bigarray.int16_signed_elt, Bigarray.c_layout) Bigarray.Array1.t -> float
+    = "__ocaml_ffi_bigarray_kinds_int16_mean"
+  ;;
+
+  external uint16_max
+    : (int, Bigarray.int16_unsigned_elt, Bigarray.c_layout) Bigarray.Array1.t -> int
+    = "__ocaml_ffi_bigarray_kinds_uint16_max"
+  ;;
+
+  external int8_abs
+    : (int, Bigarray.int8_signed_elt, Bigarray.c_layout) Bigarray.Array1.t -> (int, Bigarray.int8_signed_elt, Bigarray.c_layout) Bigarray.Array1.t
+    = "__ocaml_ffi_bigarray_kinds_int8_abs"
+  ;;
+
+  external int64_sum
+    : (int64, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t -> Int64.t
+    = "__ocaml_ffi_bigarray_kinds_int64_sum"
+  ;;
+
+  external iq_power
+    : (Complex.t, Bigarray.complex32_elt, Bigarray.c_layout) Bigarray.Array1.t -> (float, Bigarray.float32_elt, Bigarray.c_layout) Bigarray.Array1.t
+    = "__ocaml_ffi_bigarray_kinds_iq_power"
+  ;;
+
+  external complex64_conj
+    : (Complex.t, Bigarray.complex64_elt, Bigarray.c_layout) Bigarray.Array1.t -> (Complex.t, Bigarray.complex64_elt, Bigarray.c_layout) Bigarray.Array1.t
+    = "__ocaml_ffi_bigarray_kinds_complex64_conj"
+  ;;
+
+  external chars_uppercase
+    : (char, Bigarray.int8_unsigned_elt, Bigarray.c_layout) Bigarray.Array1.t -> (char, Bigarray.int8_unsigned_elt, Bigarray.c_layout) Bigarray.Array1.t
+    = "__ocaml_ffi_bigarray_kinds_chars_uppercase"
+  ;;
+
+  external ints_double
+    : (int, Bigarray.int_elt, Bigarray.c_layout) Bigarray.Array1.t -> (nativeint, Bigarray.nativeint_elt, Bigarray.c_layout) Bigarray.Array1.t
+    = "__ocaml_ffi_bigarray_kinds_ints_double"
+  ;;
+
+end
+module Ffi_bigarray_owned = struct
+  external _layout_fingerprint : unit -> string = "__ocaml_ffi_bigarray_owned__layout_fingerprint";;
+  let () =
+    let fingerprint = _layout_fingerprint () in
+    if not (String.equal fingerprint "55833e2d186bacfc") then
+      failwith ("ocaml-rust: the layout of the types in Ffi_bigarray_owned differs between OCaml (55833e2d186bacfc) and Rust (" ^ fingerprint ^ "), the bindings have to be regenerated");;
+  external range_vec
+    : int -> (float, Bigarray.float64_elt, Bigarray.c_layout) Bigarray.Array1.t
+    = "__ocaml_ffi_bigarray_owned_range_vec"
+  ;;
+
+  external squares_boxed
+    : int -> (int64, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t
+    = "__ocaml_ffi_bigarray_owned_squares_boxed"
+  ;;
+
+  external arc_matrix
+    : int -> int -> (float, Bigarray.float32_elt, Bigarray.c_layout) Bigarray.Array2.t
+    = "__ocaml_ffi_bigarray_owned_arc_matrix"
+  ;;
+
+  external tracked_buffer
+    : int -> (float, Bigarray.float64_elt, Bigarray.c_layout) Bigarray.Array1.t
+    = "__ocaml_ffi_bigarray_owned_tracked_buffer"
+  ;;
+
+  external tracked_drops
+    : unit -> (int [@untagged])
+    = "__ocaml_ffi_bigarray_owned_tracked_drops" "__ocaml_ffi_bigarray_owned_tracked_drops_unboxed"
+  ;;
+
+end
+module Ffi_bigarray_parallel = struct
+  external _layout_fingerprint : unit -> string = "__ocaml_ffi_bigarray_parallel__layout_fingerprint";;
+  let () =
+    let fingerprint = _layout_fingerprint () in
+    if not (String.equal fingerprint "fbfdb4a27586b95e") then
+      failwith ("ocaml-rust: the layout of the types in Ffi_bigarray_parallel differs between OCaml (fbfdb4a27586b95e) and Rust (" ^ fingerprint ^ "), the bindings have to be regenerated");;
+  external bigarray_scale
+    : (float, Bigarray.float64_elt, Bigarray.c_layout) Bigarray.Array1.t -> float -> unit
+    = "__ocaml_ffi_bigarray_parallel_bigarray_scale"
+  ;;
+
+  external bigarray_add
+    : (float, Bigarray.float64_elt, Bigarray.c_layout) Bigarray.Array1.t -> (float, Bigarray.float64_elt, Bigarray.c_layout) Bigarray.Array1.t -> unit
+    = "__ocaml_ffi_bigarray_parallel_bigarray_add"
+  ;;
+
 end
@@ -0,0 +1,677 @@
+exception Rust_panic of string
+
+module Rust_error : sig
+  type kind =
+    | Io
+    | Parse
+    | Other
+
+  type t =
+    { kind : kind
+    ; message : string
+    ; sources : string array
+    }
+end
+
+exception Rust_error of Rust_error.t
+
+type ('a, 'b) rust_fn1 = 'a -> 'b
+
+module Rust_base : sig
+  val map_of_alist
+    : ('k, 'cmp) Base.Comparator.Module.t -> ('k * 'v) list -> ('k, 'v, 'cmp) Base.Map.t
+
+  val map_to_alist : ('k, 'v, _) Base.Map.t -> ('k * 'v) list
+  val hashtbl_of_alist : 'k Base.Hashtbl.Key.t -> ('k * 'v) list -> ('k, 'v) Base.Hashtbl.t
+  val hashtbl_to_alist : ('k, 'v) Base.Hashtbl.t -> ('k * 'v) list
+  val set_of_list : ('a, 'cmp) Base.Comparator.Module.t -> 'a list -> ('a, 'cmp) Base.Set.t
+  val set_to_list : ('a, _) Base.Set.t -> 'a list
+end
+
+(** A point in the plane, all-float records use the flat float layout. *)
+type vec2 = {
+  x: float;
+  y: float;
+} [@@boxed]
+
+type direction =
+| North
+| East
+| South
+| West
+| Angle of float
+[@@boxed]
+
+type 't located = {
+  at: vec2;
+  value: 't;
+} [@@boxed]
+
+module Ffi : sig
+  external add_i64
+    : (Int64.t [@unboxed]) -> (Int64.t [@unboxed]) -> (Int64.t [@unboxed])
+    = "__ocaml_ffi_add_i64" "__ocaml_ffi_add_i64_unboxed"
+
+  external add_f64
+    : (float [@unboxed]) -> (float [@unboxed]) -> (float [@unboxed])
+    = "__ocaml_ffi_add_f64" "__ocaml_ffi_add_f64_unboxed"
+  [@@noalloc]
+
+  external is_even
+    : (int [@untagged]) -> bool
+    = "__ocaml_ffi_is_even" "__ocaml_ffi_is_even_unboxed"
+
+  external str_format
+    : (int * int) -> string -> string
+    = "__ocaml_ffi_str_format"
+
+  external pair
+    : (string * float * (int * int)) -> string
+    = "__ocaml_ffi_pair"
+
+  external option_result
+    : int option -> string -> (int, string) Result.t
+    = "__ocaml_ffi_option_result"
+
+  external vec_add
+    : int array -> int -> int array
+    = "__ocaml_ffi_vec_add"
+
+  external rust_panic
+    : string -> int
+    = "__ocaml_ffi_rust_panic"
+
+  external format6
+    : int -> Int64.t -> float -> string -> int option -> bool -> string
+    = "__ocaml_ffi_format6_bytecode" "__ocaml_ffi_format6"
+
+module Foo : sig
+  external add_one
+    : (int [@untagged]) -> (int [@untagged])
+    = "__ocaml_ffifoo__add_one" "__ocaml_ffifoo__add_one_unboxed"
+
+end
+end
+
+module Ffi2 : sig
+  type my_vec
+
+  external vec_new
+    : unit -> my_vec
+    = "__ocaml_ffi2_vec_new"
+
+  external vec_push
+    : my_vec -> int -> unit
+    = "__ocaml_ffi2_vec_push"
+
+  external vec_content
+    : my_vec -> Int64.t array
+    = "__ocaml_ffi2_vec_content"
+
+end
+
+module Ffi3 : sig
+open! Sexplib.Conv
+
+  type my_enum =
+  | NoArg
+  | OneArg of int
+  | TwoArgs of int * string
+  | StructArgs of { x: int; y: string }
+  | Rec of my_enum
+  [@@boxed]
+
+  type my_struct = {
+    x: int;
+    y: string;
+    z: (int * string option * float);
+    zs: float array;
+  } [@@boxed][@@deriving sexp]
+
+  external mystruct_to_string
+    : my_struct -> string
+    = "__ocaml_ffi3_mystruct_to_string"
+
+  external mystruct_add_x
+    : my_struct -> int -> my_struct
+    = "__ocaml_ffi3_mystruct_add_x"
+
+  external myenum_to_string
+    : my_enum -> string
+    = "__ocaml_ffi3_myenum_to_string"
+
+  external myenum_add_x
+    : my_enum -> int -> my_enum
+    = "__ocaml_ffi3_myenum_add_x"
+
+end
+
+module Ffi4 : sig
+  external map_callback
+    : int array -> ((int) -> (string)) -> string array
+    = "__ocaml_ffi4_map_callback"
+
+  external sum_n
+    : int -> (unit -> (int)) -> int
+    = "__ocaml_ffi4_sum_n"
+
+end
+
+module Ffi6 : sig
+  type c
+
+  external create_foo2
+    : int -> c
+    = "__ocaml_ffi6_create_foo2"
+
+  external foo2_to_string
+    : c -> string
+    = "__ocaml_ffi6_foo2_to_string"
+
+end
+
+module Ffi7 : sig
+  type compact
+
+  external generate
+    : int -> ((((Int64.t * Int64.t) * compact) * Int64.t) * Int64.t)
+    = "__ocaml_ffi7_generate"
+
+end
+
+module Ffi_double_array : sig
+open! Sexplib.Conv
+
+  type quaternion = {
+    a: float;
+    b: float;
+    c: float;
+    d: float;
+  } [@@boxed][@@deriving sexp]
+
+  external add_ones
+    : float array -> float array
+    = "__ocaml_ffi_double_array_add_ones"
+
+  external add_quat
+    : quaternion -> quaternion -> quaternion
+    = "__ocaml_ffi_double_array_add_quat"
+
+  external create_quat
+    : float -> float -> float -> float -> quaternion
+    = "__ocaml_ffi_double_array_create_quat"
+
+  external quat_norm
+    : quaternion -> float
+    = "__ocaml_ffi_double_array_quat_norm"
+
+  external float_array_sum
+    : float array -> float
+    = "__ocaml_ffi_double_array_float_array_sum"
+
+  external normalize_quats
+    : float array -> unit
+    = "__ocaml_ffi_double_array_normalize_quats"
+
+end
+
+module Ffi_ocaml : sig
+  external call_ocaml_format
+    : int -> string
+    = "__ocaml_ffi_ocaml_call_ocaml_format"
+
+  external call_ocaml_incr
+    : int -> (int, string) Result.t
+    = "__ocaml_ffi_ocaml_call_ocaml_incr"
+
+  val register_ocaml_format : (int -> string -> string) -> unit
+
+  (** Increments its argument, this can raise an exception. *)
+  val register_ocaml_incr : (int -> int) -> unit
+
+end
+
+module Ffi_errors : sig
+  external parse_int_record
+    : string -> (int, Rust_error.t) Result.t
+    = "__ocaml_ffi_errors_parse_int_record"
+
+  external parse_int_exn
+    : string -> int
+    = "__ocaml_ffi_errors_parse_int_exn"
+
+  external read_config
+    : string -> (string, Rust_error.t) Result.t
+    = "__ocaml_ffi_errors_read_config"
+
+  (** Checks that the port is in 1..65535 ( *inclusive* ), the error "kind"
+      depends on { |register_error_kinds|} having been called, '"' and "C:\\" *)
+  external check_port
+    : int -> (int, Rust_error.t) Result.t
+    = "__ocaml_ffi_errors_check_port"
+
+  external register_error_kinds
+    : unit -> unit
+    = "__ocaml_ffi_errors_register_error_kinds" "__ocaml_ffi_errors_register_error_kinds_unboxed"
+
+end
+
+module Ffi_list : sig
+  [@@@ocaml.alert "-deprecated"]
+
+open! Sexplib.Conv
+
+  (** A shape given by the list of its vertices. *)
+  type shape =
+  | Empty
+  | Polygon of (float * float) list (** The vertices are given in clockwise order. *)
+  [@@boxed][@@deriving sexp]
+
+  (** A named series of values,
+      e.g. a column from a dataframe. *)
+  type series = {
+    name: string;
+    values: float list; (** Missing values are represented by nan. *)
+  } [@@boxed][@@deriving sexp]
+
+  (** Adds `y` to all the elements of the list. *)
+  external list_add
+    : int list -> int -> int list
+    = "__ocaml_ffi_list_list_add"
+
+  external list_incr
+    : int list -> int list
+    = "__ocaml_ffi_list_list_incr"
+  [@@ocaml.deprecated "use list_add instead"]
+
+  external list_to_f32
+    : float list -> float list
+    = "__ocaml_ffi_list_list_to_f32"
+
+  external series_scale
+    : series -> float -> series
+    = "__ocaml_ffi_list_series_scale"
+
+  external shape_translate
+    : shape -> float -> float -> shape
+    = "__ocaml_ffi_list_shape_translate"
+
+  external list_callback
+    : int -> ((int list) -> (int)) -> int
+    = "__ocaml_ffi_list_list_callback"
+
+end
+
+module Ffi_closures : sig
+  external fold_left
+    : int array -> string -> ((string) -> (int) -> (string)) -> string
+    = "__ocaml_ffi_closures_fold_left"
+
+  external call_with4
+    : ((int) -> (float) -> (string) -> (bool) -> (string)) -> string
+    = "__ocaml_ffi_closures_call_with4"
+
+end
+
+module Ffi_rust_fn : sig
+  external counter
+    : int -> ((unit), (int)) rust_fn1
+    = "__ocaml_ffi_rust_fn_counter"
+
+  external splitter
+    : string -> ((string), (string array)) rust_fn1
+    = "__ocaml_ffi_rust_fn_splitter"
+
+  external comparator
+    : bool -> (((int * int)), (int)) rust_fn1
+    = "__ocaml_ffi_rust_fn_comparator"
+
+  external checked_sqrt
+    : unit -> ((float), (float)) rust_fn1
+    = "__ocaml_ffi_rust_fn_checked_sqrt"
+
+  external incr_after
+    : ((int) -> (int)) -> ((int), (int)) rust_fn1
+    = "__ocaml_ffi_rust_fn_incr_after"
+
+end
+
+module Ffi_exn : sig
+  external describe_exn
+    : (unit -> (unit)) -> string
+    = "__ocaml_ffi_exn_describe_exn"
+
+  external double_or_raise
+    : ((int) -> (int)) -> int -> int
+    = "__ocaml_ffi_exn_double_or_raise"
+
+end
+
+module Ffi_derive : sig
+  external vec2_move
+    : vec2 -> direction -> float -> vec2
+    = "__ocaml_ffi_derive_vec2_move"
+
+  external vec2_direction
+    : vec2 -> direction
+    = "__ocaml_ffi_derive_vec2_direction"
+
+  external located_move
+    : string located -> direction -> string located
+    = "__ocaml_ffi_derive_located_move"
+
+end
+
+module Ffi_newtypes : sig
+  type meters = Meters of float [@@unboxed]
+
+  type user_id = UserId of int [@@unboxed]
+
+  type seconds = Seconds of float [@@boxed]
+
+  type pair = Pair of int * string [@@boxed]
+
+  type marker = unit
+
+  external meters_add
+    : meters -> meters -> meters
+    = "__ocaml_ffi_newtypes_meters_add"
+
+  external meters_double
+    : meters -> meters
+    = "__ocaml_ffi_newtypes_meters_double"
+
+  external speed
+    : meters -> seconds -> float
+    = "__ocaml_ffi_newtypes_speed"
+
+  external user_ids
+    : user_id -> int -> user_id array
+    = "__ocaml_ffi_newtypes_user_ids"
+
+  external pair_swap
+    : pair -> pair
+    = "__ocaml_ffi_newtypes_pair_swap"
+
+  external marker_name
+    : marker -> string
+    = "__ocaml_ffi_newtypes_marker_name"
+
+  external marker_new
+    : unit -> marker
+    = "__ocaml_ffi_newtypes_marker_new"
+
+end
+
+module Ffi_generics : sig
+  type 't tagged = {
+    tag: string;
+    value: 't;
+  } [@@boxed]
+
+  type 't tree =
+  | Leaf
+  | Node of 't tree * 't * 't tree
+  [@@boxed]
+
+  external tagged_int
+    : string -> int -> int tagged
+    = "__ocaml_ffi_generics_tagged_int"
+
+  external tagged_describe
+    : float array tagged -> string
+    = "__ocaml_ffi_generics_tagged_describe"
+
+  external tree_of_list
+    : int array -> int tree
+    = "__ocaml_ffi_generics_tree_of_list"
+
+  external tree_sum
+    : int tree -> int
+    = "__ocaml_ffi_generics_tree_sum"
+
+  external tree_lengths
+    : string tree -> int tagged tree
+    = "__ocaml_ffi_generics_tree_lengths"
+
+end
+
+module Ffi_polyvar : sig
+open! Sexplib.Conv
+
+  type open_flag =
+  [ `Read
+  | `Write
+  | `Append
+  | `Mode of int
+  | `Rename of string * string
+  ] [@@deriving sexp]
+
+  external open_flags_default
+    : unit -> open_flag array
+    = "__ocaml_ffi_polyvar_open_flags_default"
+
+  external open_flags_describe
+    : open_flag array -> string
+    = "__ocaml_ffi_polyvar_open_flags_describe"
+
+  external open_flag_bump
+    : open_flag -> open_flag
+    = "__ocaml_ffi_polyvar_open_flag_bump"
+
+end
+
+module Ffi_maps : sig
+open! Sexplib.Conv
+
+  type metadata = {
+    name: string;
+    entries: (string * string) list;
+  } [@@boxed][@@deriving sexp]
+
+  external word_counts
+    : string -> (string * int) list
+    = "__ocaml_ffi_maps_word_counts"
+
+  external merge_counts
+    : (string * int) list -> (string * int) list -> (string * int) list
+    = "__ocaml_ffi_maps_merge_counts"
+
+  external unique_sorted
+    : int array -> int list
+    = "__ocaml_ffi_maps_unique_sorted"
+
+  external set_contains
+    : string list -> string -> bool
+    = "__ocaml_ffi_maps_set_contains"
+
+  external metadata_set
+    : metadata -> string -> string -> metadata
+    = "__ocaml_ffi_maps_metadata_set"
+
+  module With_base : sig
+    val word_counts : string -> (string, int, Base.String.comparator_witness) Base.Map.t
+    val merge_counts : (string, int) Base.Hashtbl.t -> (string, int) Base.Hashtbl.t -> (string, int, Base.String.comparator_witness) Base.Map.t
+    val unique_sorted : int array -> (int, Base.Int.comparator_witness) Base.Set.t
+    val set_contains : string Base.Hash_set.t -> string -> bool
+  end
+
+end
+
+module Ffi_bytes : sig
+  external bytes_xor
+    : bytes -> int -> unit
+    = "__ocaml_ffi_bytes_bytes_xor"
+
+  external bytes_checksum
+    : string -> int
+    = "__ocaml_ffi_bytes_bytes_checksum"
+
+  external bytes_copy
+    : string -> bytes -> int
+    = "__ocaml_ffi_bytes_bytes_copy"
+
+  external bytes_reverse
+    : bytes -> bytes
+    = "__ocaml_ffi_bytes_bytes_reverse"
+
+  external lossy_length
+    : string -> int
+    = "__ocaml_ffi_bytes_lossy_length"
+
+  external utf8_length
+    : string -> int
+    = "__ocaml_ffi_bytes_utf8_length"
+
+  external os_string_len
+    : string -> int
+    = "__ocaml_ffi_bytes_os_string_len"
+
+  external path_join
+    : string -> string -> string
+    = "__ocaml_ffi_bytes_path_join"
+
+end
+
+module Ffi_borrowed : sig
+  external str_word_count
+    : string -> int
+    = "__ocaml_ffi_borrowed_str_word_count"
+
+  external str_contains
+    : string -> string -> bool
+    = "__ocaml_ffi_borrowed_str_contains"
+
+  external floats_sum
+    : float array -> float
+    = "__ocaml_ffi_borrowed_floats_sum"
+
+  external floats_scale
+    : float array -> float -> unit
+    = "__ocaml_ffi_borrowed_floats_scale"
+
+  external ints_sum
+    : int array -> int
+    = "__ocaml_ffi_borrowed_ints_sum"
+
+  external ints_incr
+    : int array -> unit
+    = "__ocaml_ffi_borrowed_ints_incr"
+
+  external str_len_with
+    : string -> ((int) -> (int)) -> int
+    = "__ocaml_ffi_borrowed_str_len_with"
+
+  external str_to_rooted
+    : string -> int
+    = "__ocaml_ffi_borrowed_str_to_rooted"
+
+end
+
+module Ffi_bigarray : sig
+  external matrix_transpose
+    : (float, Bigarray.float64_elt, Bigarray.c_layout) Bigarray.Array2.t -> (float, Bigarray.float64_elt, Bigarray.c_layout) Bigarray.Array2.t
+    = "__ocaml_ffi_bigarray_matrix_transpose"
+
+  external matrix_trace
+    : (float, Bigarray.float64_elt, Bigarray.fortran_layout) Bigarray.Array2.t -> float
+    = "__ocaml_ffi_bigarray_matrix_trace"
+
+  external matrix_strides
+    : (float, Bigarray.float64_elt, Bigarray.fortran_layout) Bigarray.Array2.t -> int array
+    = "__ocaml_ffi_bigarray_matrix_strides"
+
+  external cube_new
+    : int -> (float, Bigarray.float32_elt, Bigarray.fortran_layout) Bigarray.Array3.t
+    = "__ocaml_ffi_bigarray_cube_new"
+
+  external genarray_shape
+    : (float, Bigarray.float64_elt, Bigarray.c_layout) Bigarray.Genarray.t -> int array
+    = "__ocaml_ffi_bigarray_genarray_shape"
+
+  external genarray_sum
+    : (float, Bigarray.float64_elt, Bigarray.c_layout) Bigarray.Genarray.t -> float
+    = "__ocaml_ffi_bigarray_genarray_sum"
+
+  external matrix_row_sums
+    : (float, Bigarray.float64_elt, Bigarray.fortran_layout) Bigarray.Array2.t -> float array
+    = "__ocaml_ffi_bigarray_matrix_row_sums"
+
+  external genarray_scale
+    : (float, Bigarray.float64_elt, Bigarray.c_layout) Bigarray.Genarray.t -> float -> unit
+    = "__ocaml_ffi_bigarray_genarray_scale"
+
+  external bigarray_copy
+    : (float, Bigarray.float64_elt, Bigarray.c_layout) Bigarray.Array1.t -> (float, Bigarray.float64_elt, Bigarray.c_layout) Bigarray.Array1.t -> unit
+    = "__ocaml_ffi_bigarray_bigarray_copy"
+
+end
+
+module Ffi_bigarray_kinds : sig
+  external int16_mean
+    : (int, Bigarray.int16_signed_elt, Bigarray.c_layout) Bigarray.Array1.t -> float
+    = "__ocaml_ffi_bigarray_kinds_int16_mean"
+
+  external uint16_max
+    : (int, Bigarray.int16_unsigned_elt, Bigarray.c_layout) Bigarray.Array1.t -> int
+    = "__ocaml_ffi_bigarray_kinds_uint16_max"
+
+  external int8_abs
+    : (int, Bigarray.int8_signed_elt, Bigarray.c_layout) Bigarray.Array1.t -> (int, Bigarray.int8_signed_elt, Bigarray.c_layout) Bigarray.Array1.t
+    = "__ocaml_ffi_bigarray_kinds_int8_abs"
+
+  external int64_sum
+    : (int64, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t -> Int64.t
+    = "__ocaml_ffi_bigarray_kinds_int64_sum"
+
+  external iq_power
+    : (Complex.t, Bigarray.complex32_elt, Bigarray.c_layout) Bigarray.Array1.t -> (float, Bigarray.float32_elt, Bigarray.c_layout) Bigarray.Array1.t
+    = "__ocaml_ffi_bigarray_kinds_iq_power"
+
+  external complex64_conj
+    : (Complex.t, Bigarray.complex64_elt, Bigarray.c_layout) Bigarray.Array1.t -> (Complex.t, Bigarray.complex64_elt, Bigarray.c_layout) Bigarray.Array1.t
+    = "__ocaml_ffi_bigarray_kinds_complex64_conj"
+
+  external chars_uppercase
+    : (char, Bigarray.int8_unsigned_elt, Bigarray.c_layout) Bigarray.Array1.t -> (char, Bigarray.int8_unsigned_elt, Bigarray.c_layout) Bigarray.Array1.t
+    = "__ocaml_ffi_bigarray_kinds_chars_uppercase"
+
+  external ints_double
+    : (int, Bigarray.int_elt, Bigarray.c_layout) Bigarray.Array1.t -> (nativeint, Bigarray.nativeint_elt, Bigarray.c_layout) Bigarray.Array1.t
+    = "__ocaml_ffi_bigarray_kinds_ints_double"
+
+end
+
+module Ffi_bigarray_owned : sig
+  external range_vec
+    : int -> (float, Bigarray.float64_elt, Bigarray.c_layout) Bigarray.Array1.t
+    = "__ocaml_ffi_bigarray_owned_range_vec"
+
+  external squares_boxed
+    : int -> (int64, Bigarray.int64_elt, Bigarray.c_layout) Bigarray.Array1.t
+    = "__ocaml_ffi_bigarray_owned_squares_boxed"
+
+  external arc_matrix
+    : int -> int -> (float, Bigarray.float32_elt, Bigarray.c_layout) Bigarray.Array2.t
+    = "__ocaml_ffi_bigarray_owned_arc_matrix"
+
+  external tracked_buffer
+    : int -> (float, Bigarray.float64_elt, Bigarray.c_layout) Bigarray.Array1.t
+    = "__ocaml_ffi_bigarray_owned_tracked_buffer"
+
+  external tracked_drops
+    : unit -> (int [@untagged])
+    = "__ocaml_ffi_bigarray_owned_tracked_drops" "__ocaml_ffi_bigarray_owned_tracked_drops_unboxed"
+
+end
+
+module Ffi_bigarray_parallel : sig
+  external bigarray_scale
+    : (float, Bigarray.float64_elt, Bigarray.c_layout) Bigarray.Array1.t -> float -> unit
+    = "__ocaml_ffi_bigarray_parallel_bigarray_scale"
+
+  external bigarray_add
+    : (float, Bigarray.float64_elt, Bigarray.c_layout) Bigarray.Array1.t -> (float, Bigarray.float64_elt, Bigarray.c_layout) Bigarray.Array1.t -> unit
+    = "__ocaml_ffi_bigarray_parallel_bigarray_add"
+
+end
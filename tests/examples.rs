@@ -0,0 +1,598 @@
+// Checks the example crates by generating their OCaml bindings and running OCaml
+// snippets against them, these mirror the expect tests from tests/basic and
+// tests/arrow. The tests are skipped when no OCaml toolchain is available,
+// unless OCAML_RUST_TESTS or CI is set.
+use ocaml_rust::testing::Harness;
+use std::path::PathBuf;
+
+fn workspace_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+fn cargo() -> String {
+    std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string())
+}
+
+// The example crates are built with the same profile as the tests, in the same
+// target directory.
+fn target_dir() -> PathBuf {
+    match std::env::var_os("CARGO_TARGET_DIR") {
+        Some(dir) => workspace_dir().join(dir),
+        None => workspace_dir().join("target"),
+    }
+}
+
+fn profile() -> &'static str {
+    if cfg!(debug_assertions) {
+        "debug"
+    } else {
+        "release"
+    }
+}
+
+fn build(package: &str) {
+    static BUILT: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(vec![]);
+    let mut built = BUILT.lock().unwrap_or_else(|err| err.into_inner());
+    if built.iter().any(|p| p == package) {
+        return;
+    }
+    let mut cmd = std::process::Command::new(cargo());
+    cmd.args(["build", "-q", "-p", package]).arg("--target-dir").arg(target_dir());
+    if profile() == "release" {
+        cmd.arg("--release");
+    }
+    let status = cmd.current_dir(workspace_dir()).status().unwrap();
+    assert!(status.success(), "cannot build {package}");
+    built.push(package.to_string())
+}
+
+fn harness(package: &str, crate_dir: &str) -> Option<Harness> {
+    if !Harness::is_available() {
+        let required = ["OCAML_RUST_TESTS", "CI"].iter().any(|v| std::env::var_os(v).is_some());
+        if required {
+            panic!("ocamlfind not found")
+        }
+        eprintln!("ocamlfind not found, skipping");
+        return None;
+    }
+    build(package);
+    let dir = workspace_dir();
+    let static_lib =
+        target_dir().join(profile()).join(format!("lib{}.a", package.replace('-', "_")));
+    let harness = Harness::new(dir.join(crate_dir).join("src/lib.rs"), static_lib)
+        .generator(&[&cargo(), "run", "-q", "-p", "ocaml-rust-cmd", "--"])
+        .package("sexplib")
+        .build_dir(target_dir().join("ocaml-rust-testing"));
+    Some(harness)
+}
+
+fn expect_in(package: &str, crate_dir: &str, snippet: &str, expected: &str) {
+    if let Some(harness) = harness(package, crate_dir) {
+        if let Err(err) = harness.expect(snippet, expected) {
+            panic!("{}", err)
+        }
+    }
+}
+
+fn expect(snippet: &str, expected: &str) {
+    expect_in("ocaml-rust-example", "example", snippet, expected)
+}
+
+#[test]
+fn scalars() {
+    expect(
+        r#"
+        let () = Printf.printf "%d\n" (Ffi.Foo.add_one 41);
+        Printf.printf "%Ld\n" (Ffi.add_i64 20L 22L);
+        Printf.printf "%.2f %b\n" (Ffi.add_f64 1.25 2.5) (Ffi.is_even 42);
+        Printf.printf "<%s>\n" (Ffi.pair ("foobar", 3.14159265358979, (1337, 299792458)));
+        Printf.printf "= %s =\n" (Ffi.str_format (42, -1337) "bar baz");
+        Array.iter (Printf.printf "%d ") (Ffi.vec_add [| 3; 1; 4 |] (-1));
+        print_newline ();
+        Printf.printf "%s\n" (Ffi.format6 1 2L 3.5 "four" (Some 5) true);
+        (match Ffi.option_result None "foo" with
+         | Ok v -> Printf.printf "ok %d\n" v
+         | Error err -> Printf.printf "error %s\n" err);
+        match Ffi.rust_panic "boom" with
+        | _ -> ()
+        | exception Rust_panic msg -> print_endline msg
+        "#,
+        "
+        42
+        42
+        3.75 true
+        <foobar:3.14159265358979:1337:299792458>
+        = foo<42|-1337>: bar baz =
+        2 0 3
+        1 2 3.5 four Some(5) true
+        error foo
+        panicked at 'boom', example/src/lib.rs:42:5
+        ",
+    )
+}
+
+#[test]
+fn custom_types() {
+    expect(
+        r#"
+        let () =
+          let v = Ffi2.vec_new () in
+          for i = 1 to 5 do
+            Ffi2.vec_push v i
+          done;
+          Gc.compact ();
+          Array.iter (Printf.printf "%Ld ") (Ffi2.vec_content v);
+          print_newline ();
+          let foo1 = Ffi6.create_foo2 42 in
+          let foo2 = Ffi6.create_foo2 1337 in
+          Printf.printf "%s\n%!" (Ffi6.foo2_to_string foo1);
+          Printf.printf "%s\n%!" (Ffi6.foo2_to_string foo2);
+          Gc.compact ();
+          Printf.printf "%s\n%!" (Ffi6.foo2_to_string foo1);
+          Gc.compact ();
+          let (((a, b), _c), d), e = Ffi7.generate 1664 in
+          Printf.printf "%Ld %Ld %Ld %Ld\n%!" a b d e
+        "#,
+        "
+        1 2 3 4 5
+        v: Foo { v: 42 }
+        v: Foo { v: 1337 }
+        dropping foo 1337
+        v: Foo { v: 42 }
+        dropping foo 42
+        Rust: 1664 0 0 1 664
+        0 0 1 664
+        ",
+    )
+}
+
+#[test]
+fn structs_and_enums() {
+    expect(
+        r#"
+        let () =
+          let t = { Ffi3.x = 42; y = "foo"; z = 1337, None, 3.14; zs = [| 2.5 |] } in
+          Printf.printf "<%s>\n" (Ffi3.mystruct_to_string (Ffi3.mystruct_add_x t 1337));
+          List.iter
+            (fun m ->
+              let s1 = Ffi3.myenum_to_string m in
+              let s2 = Ffi3.myenum_add_x m 42 |> Ffi3.myenum_to_string in
+              Printf.printf "<%s> <%s>\n" s1 s2)
+            Ffi3.[ NoArg; OneArg 42; TwoArgs (1337, "FooBar"); Rec (StructArgs { x = 1; y = "a" }) ]
+        "#,
+        r#"
+        <MyStruct { x: 1379, y: "foo", z: (1337, None, 3.14), zs: [2.5] }>
+        <NoArg> <NoArg>
+        <OneArg(42)> <OneArg(84)>
+        <TwoArgs(1337, "FooBar")> <TwoArgs(1379, "FooBar")>
+        <Rec(StructArgs { x: 1, y: "a" })> <Rec(StructArgs { x: 43, y: "a" })>
+        "#,
+    )
+}
+
+#[test]
+fn double_arrays() {
+    expect(
+        r#"
+        let () =
+          Array.iter (Printf.printf "%.2f ") (Ffi_double_array.add_ones [| 3.14; 15.92 |]);
+          print_newline ();
+          let q = Ffi_double_array.create_quat 1. 2. (-3.) (-4.) in
+          Printf.printf "%.0f %.0f %.0f %.0f\n" q.Ffi_double_array.a q.b q.c q.d;
+          let q = { Ffi_double_array.a = 1.; b = 2.; c = 2.; d = 4. } in
+          Printf.printf "%.1f\n" (Ffi_double_array.quat_norm q);
+          Printf.printf "%.1f\n" (Ffi_double_array.float_array_sum [| 1.5; 2.5; 3. |]);
+          let qs = [| 1.; 2.; 2.; 4. |] in
+          Ffi_double_array.normalize_quats qs;
+          Array.iter (Printf.printf "%.1f ") qs;
+          print_newline ();
+          try Ffi_double_array.normalize_quats [| 1.; 2.; 3. |] with
+          | Invalid_argument msg -> Printf.printf "invalid argument: %s\n" msg
+        "#,
+        "
+        4.14 16.92
+        1 2 -3 -4
+        5.0
+        7.0
+        0.2 0.4 0.4 0.8
+        invalid argument: float array length 3 is not a multiple of 4
+        ",
+    )
+}
+
+#[test]
+fn calling_ocaml() {
+    expect(
+        r#"
+        let () =
+          Ffi_ocaml.register_ocaml_format (Printf.sprintf "%d:%s");
+          Ffi_ocaml.register_ocaml_incr (fun x -> if x < 0 then failwith "negative" else x + 1);
+          Printf.printf "%s\n" (Ffi_ocaml.call_ocaml_format 42);
+          List.iter
+            (fun x ->
+              match Ffi_ocaml.call_ocaml_incr x with
+              | Ok v -> Printf.printf "ok %d\n" v
+              | Error err -> Printf.printf "error %s\n" err)
+            [ 41; -1 ]
+        "#,
+        r#"
+        42:from-rust
+        ok 42
+        error ocaml exn: Failure("negative")
+        "#,
+    )
+}
+
+#[test]
+fn errors() {
+    expect(
+        r#"
+        let print_error (err : Rust_error.t) =
+          let kind =
+            match err.kind with
+            | Io -> "io"
+            | Parse -> "parse"
+            | Other -> "other"
+          in
+          Printf.printf "%s: %s [%s]\n" kind err.message
+            (String.concat "; " (Array.to_list err.sources))
+
+        let print_result = function
+          | Ok v -> Printf.printf "%d\n" v
+          | Error err -> print_error err
+
+        let () =
+          print_result (Ffi_errors.parse_int_record "42");
+          print_result (Ffi_errors.parse_int_record "foo");
+          (match Ffi_errors.parse_int_exn "foo" with
+           | v -> Printf.printf "%d\n" v
+           | exception Rust_error err -> print_error err);
+          (match Ffi_errors.read_config "/does/not/exist" with
+           | Ok _ -> print_endline "unexpected success"
+           | Error err -> print_error err);
+          print_result (Ffi_errors.check_port 0);
+          Ffi_errors.register_error_kinds ();
+          print_result (Ffi_errors.check_port 0);
+          print_result (Ffi_errors.check_port 8080)
+        "#,
+        "
+        42
+        parse: invalid digit found in string []
+        parse: invalid digit found in string []
+        io: cannot read config [No such file or directory (os error 2)]
+        other: invalid port 0 []
+        parse: invalid port 0 []
+        8080
+        ",
+    )
+}
+
+#[test]
+fn lists_and_closures() {
+    expect(
+        r#"
+        let () =
+          List.iter (Printf.printf "%d ") (Ffi_list.list_add [ 1; 2; 3 ] 10);
+          print_newline ();
+          List.iter (Printf.printf "%.2f ") (Ffi_list.list_to_f32 [ 0.5; 1.25; -3. ]);
+          print_newline ();
+          (match Ffi_list.shape_translate (Ffi_list.Polygon [ 0., 0.; 1., 0. ]) 1. 2. with
+           | Ffi_list.Polygon ps -> List.iter (fun (x, y) -> Printf.printf "(%.0f %.0f) " x y) ps
+           | _ -> print_string "unexpected shape");
+          print_newline ();
+          Printf.printf "%d\n" (Ffi_list.list_callback 5 (List.fold_left ( + ) 0));
+          Array.iter (Printf.printf "%s ") (Ffi4.map_callback [| 3; 1; 4 |] (Printf.sprintf "<%d>"));
+          print_newline ();
+          let r = ref 0 in
+          Printf.printf "%d\n" (Ffi4.sum_n 20 (fun () -> incr r; !r));
+          print_endline
+            (Ffi_closures.fold_left [| 1; 2; 3 |] "acc" (Printf.sprintf "(%s+%d)"));
+          print_endline
+            (Ffi_closures.call_with4 (fun i f s b -> Printf.sprintf "%d %.2f %s %b" i f s b))
+        "#,
+        "
+        11 12 13
+        0.50 1.25 -3.00
+        (1 2) (2 2)
+        10
+        <3> <1> <4>
+        210
+        (((acc+1)+2)+3)
+        42 2.50 foo true
+        ",
+    )
+}
+
+#[test]
+fn rust_closures() {
+    expect(
+        r#"
+        let () =
+          let next = Ffi_rust_fn.counter 10 in
+          let v1 = next () in
+          let v2 = next () in
+          Printf.printf "%d %d\n" v1 v2;
+          Array.iter (Printf.printf "%s;") (Ffi_rust_fn.splitter ", " "a, b, c");
+          print_newline ();
+          let cmp = Ffi_rust_fn.comparator true in
+          List.iter (Printf.printf "%d ") (List.sort (fun x y -> cmp (x, y)) [ 3; 1; 4; 1; 5 ]);
+          print_newline ();
+          Gc.full_major ();
+          Printf.printf "%d\n" (next ());
+          let sqrt = Ffi_rust_fn.checked_sqrt () in
+          (match sqrt (-1.) with
+           | v -> Printf.printf "%f\n" v
+           | exception Rust_panic msg -> print_endline msg);
+          Printf.printf "%f\n" (sqrt 4.)
+        "#,
+        "
+        10 11
+        a;b;c;
+        5 4 3 1 1
+        12
+        panicked at 'negative input', example/src/lib.rs:488:13
+        2.000000
+        ",
+    )
+}
+
+#[test]
+fn ocaml_exceptions() {
+    expect(
+        r#"
+        exception Foo of int
+
+        let () = Callback.register_exception "test.foo" (Foo 0)
+
+        let () =
+          print_endline (Ffi_exn.describe_exn (fun () -> ()));
+          print_endline (Ffi_exn.describe_exn (fun () -> raise Not_found));
+          print_endline (Ffi_exn.describe_exn (fun () -> raise (Foo 42)));
+          let f x = if x < 0 then raise (Foo x) else x + 1 in
+          List.iter
+            (fun x ->
+              match Ffi_exn.double_or_raise f x with
+              | v -> Printf.printf "%d\n" v
+              | exception Foo x -> Printf.printf "Foo %d\n" x)
+            [ 20; -3 ]
+        "#,
+        "
+        no exception
+        Not_found args=0
+        Foo args=1 payload=42
+        42
+        Foo -3
+        ",
+    )
+}
+
+#[test]
+fn derived_types() {
+    expect(
+        r#"
+        let () =
+          let print_vec2 { x; y } = Printf.printf "%.2f %.2f\n" x y in
+          let v = { x = 1.; y = 2. } in
+          print_vec2 (Ffi_derive.vec2_move v North 1.);
+          print_vec2 (Ffi_derive.vec2_move v (Angle 0.) 0.5);
+          let l = Ffi_derive.located_move { at = v; value = "here" } East in
+          Printf.printf "%s at %.2f %.2f\n" l.value l.at.x l.at.y;
+          match Ffi_derive.vec2_direction { x = 1.; y = 1. } with
+          | Angle a -> Printf.printf "angle %.4f\n" a
+          | _ -> print_endline "unexpected direction"
+        "#,
+        "
+        1.00 3.00
+        1.50 2.00
+        here at 2.00 2.00
+        angle 0.7854
+        ",
+    )
+}
+
+#[test]
+fn newtypes_and_generics() {
+    expect(
+        r#"
+        let () =
+          let (Ffi_newtypes.Meters m) =
+            Ffi_newtypes.meters_add (Ffi_newtypes.Meters 1.5) (Ffi_newtypes.Meters 2.25)
+          in
+          Printf.printf "%.2f\n" m;
+          let (Ffi_newtypes.Meters m) = Ffi_newtypes.meters_double (Ffi_newtypes.Meters m) in
+          Printf.printf "%.2f\n" m;
+          Ffi_newtypes.user_ids (Ffi_newtypes.UserId 41) 2
+          |> Array.iter (fun (Ffi_newtypes.UserId id) -> Printf.printf "%d\n" id);
+          let (Ffi_newtypes.Pair (x, s)) = Ffi_newtypes.pair_swap (Ffi_newtypes.Pair (42, "abc")) in
+          Printf.printf "%d %s\n" x s;
+          print_endline (Ffi_newtypes.marker_name (Ffi_newtypes.marker_new ()));
+          let t = Ffi_generics.tagged_int "answer" 42 in
+          Printf.printf "%s %d\n" t.Ffi_generics.tag t.value;
+          let tree = Ffi_generics.tree_of_list [| 5; 3; 8 |] in
+          Printf.printf "%d\n" (Ffi_generics.tree_sum tree)
+        "#,
+        "
+        3.75
+        7.50
+        41
+        42
+        -42 cba
+        Marker
+        answer 42
+        16
+        ",
+    )
+}
+
+#[test]
+fn polymorphic_variants() {
+    expect(
+        r#"
+        let () =
+          print_endline
+            (Ffi_polyvar.open_flags_describe [| `Write; `Rename ("a", "b"); `Append |]);
+          List.iter
+            (fun flag ->
+              match Ffi_polyvar.open_flag_bump flag with
+              | `Read -> print_endline "read"
+              | `Write -> print_endline "write"
+              | `Append -> print_endline "append"
+              | `Mode m -> Printf.printf "mode %d\n" m
+              | `Rename (src, dst) -> Printf.printf "rename %s %s\n" src dst)
+            [ `Read; `Append; `Mode 1; `Rename ("a", "b") ]
+        "#,
+        r#"
+        Write,Rename("a", "b"),Append
+        write
+        read
+        mode 2
+        rename b a
+        "#,
+    )
+}
+
+#[test]
+fn maps_and_sets() {
+    expect(
+        r#"
+        let () =
+          List.iter (fun (k, v) -> Printf.printf "%s=%d " k v) (Ffi_maps.word_counts "b a c a b a");
+          print_newline ();
+          List.iter (Printf.printf "%d ") (Ffi_maps.unique_sorted [| 3; 1; 2; 3; 1 |]);
+          print_newline ();
+          Printf.printf "%b %b\n"
+            (Ffi_maps.set_contains [ "a"; "b" ] "a")
+            (Ffi_maps.set_contains [ "a"; "b" ] "c");
+          match Ffi_maps.merge_counts [ "x", 1; "x", 2 ] [] with
+          | _ -> print_endline "unexpected success"
+          | exception Invalid_argument msg -> print_endline msg
+        "#,
+        "
+        a=3 b=2 c=1
+        1 2 3
+        true false
+        duplicate element in list converted to HashMap
+        ",
+    )
+}
+
+#[test]
+fn bytes_and_borrowed_arguments() {
+    expect(
+        r#"
+        let () =
+          let b = Bytes.of_string "abc" in
+          Ffi_bytes.bytes_xor b 1;
+          Printf.printf "%S\n" (Bytes.to_string b);
+          let dst = Bytes.make 3 '.' in
+          Printf.printf "%d %S\n" (Ffi_bytes.bytes_copy "hello" dst) (Bytes.to_string dst);
+          Printf.printf "%S\n" (Ffi_bytes.path_join "/tmp" "a\xffb");
+          Printf.printf "%d\n" (Ffi_borrowed.str_word_count "the quick  brown fox");
+          let xs = [| 1.; 2.5; -4. |] in
+          Ffi_borrowed.floats_scale xs 2.;
+          Array.iter (Printf.printf "%.0f ") xs;
+          print_newline ();
+          let xs = [| 1; -2; 40 |] in
+          Ffi_borrowed.ints_incr xs;
+          Printf.printf "%d\n" (Ffi_borrowed.ints_sum xs);
+          (match Ffi_borrowed.str_word_count "a\xff" with
+           | v -> Printf.printf "%d\n" v
+           | exception Invalid_argument msg -> Printf.printf "invalid argument: %s\n" msg);
+          (match Ffi_borrowed.str_len_with "abc" (fun x -> x + 1) with
+           | v -> Printf.printf "%d\n" v
+           | exception Rust_panic msg -> print_endline msg);
+          match Ffi_borrowed.str_to_rooted "abc" with
+          | v -> Printf.printf "%d\n" v
+          | exception Rust_panic msg -> print_endline msg
+        "#,
+        r#"
+        "`cb"
+        3 "hel"
+        "/tmp/a\255b"
+        4
+        2 5 -8
+        42
+        invalid argument: invalid UTF-8 string: invalid utf-8 sequence of 1 bytes from index 1
+        panicked at 'cannot call an OCaml closure while borrowed arguments are live', src/borrowed.rs:238:9
+        panicked at 'cannot allocate OCaml values while borrowed arguments are live', src/borrowed.rs:238:9
+        "#,
+    )
+}
+
+#[test]
+fn bigarrays() {
+    expect(
+        r#"
+        let () =
+          let m =
+            Bigarray.Array2.of_array Bigarray.Float64 Bigarray.C_layout
+              [| [| 1.; 2.; 3. |]; [| 4.; 5.; 6. |] |]
+          in
+          let t = Ffi_bigarray.matrix_transpose m in
+          Printf.printf "%d %d %.0f\n" (Bigarray.Array2.dim1 t) (Bigarray.Array2.dim2 t) t.{2, 0};
+          let f =
+            Bigarray.Array2.of_array Bigarray.Float64 Bigarray.Fortran_layout
+              [| [| 1.; 2. |]; [| 3.; 4. |] |]
+          in
+          Printf.printf "%.0f\n" (Ffi_bigarray.matrix_trace f);
+          Array.iter (Printf.printf "%.0f ") (Ffi_bigarray.matrix_row_sums f);
+          print_newline ();
+          let xs = Bigarray.Array1.of_array Bigarray.Int16_signed Bigarray.C_layout [| -3; 1; 5; 1 |] in
+          Printf.printf "%.2f\n" (Ffi_bigarray_kinds.int16_mean xs);
+          let a = Bigarray.Array1.of_array Bigarray.Float64 Bigarray.C_layout [| 1.; 2.; 3.; 4. |] in
+          let sub = Bigarray.Array1.sub in
+          Ffi_bigarray_parallel.bigarray_add (sub a 0 2) (sub a 2 2);
+          Printf.printf "%.0f %.0f\n" a.{0} a.{1};
+          (try Ffi_bigarray.bigarray_copy a a with
+           | Invalid_argument msg -> Printf.printf "invalid argument: %s\n" msg);
+          let drops = Ffi_bigarray_owned.tracked_drops () in
+          let check_sub () =
+            let sub = sub (Ffi_bigarray_owned.tracked_buffer 100) 10 5 in
+            Gc.full_major ();
+            Printf.printf "%d %.1f\n" (Ffi_bigarray_owned.tracked_drops () - drops) sub.{4}
+          in
+          check_sub ();
+          Gc.full_major ();
+          Printf.printf "%d\n" (Ffi_bigarray_owned.tracked_drops () - drops)
+        "#,
+        "
+        3 2 3
+        5
+        3 7
+        1.00
+        4 6
+        invalid argument: dst and src must not overlap
+        0 1.5
+        1
+        ",
+    )
+}
+
+#[test]
+fn arrow() {
+    expect_in(
+        "ocaml-rust-arrow",
+        "example-arrow",
+        r#"
+        let () =
+          let ints = Arrow.Array_i64.from [| 1L; 2L; 40L |] in
+          (match Arrow.Array_i64.values ints 0L with
+           | Some vs -> Array.iter (Printf.printf "%Ld ") vs; print_newline ()
+           | None -> print_endline "none");
+          (match Arrow.Array_i64.values_ba ints 0L with
+           | Some ba -> Printf.printf "%d %Ld\n" (Bigarray.Array1.dim ba) ba.{2}
+           | None -> print_endline "none");
+          match Arrow.record_batch_create [| "ints", ints |] with
+          | Ok batch ->
+            Printf.printf "%d %d\n"
+              (Arrow.record_batch_num_rows batch)
+              (Arrow.record_batch_num_columns batch)
+          | Error err -> print_endline err
+        "#,
+        "
+        1 2 40
+        3 40
+        3 1
+        ",
+    )
+}